@@ -0,0 +1,106 @@
+use crate::{
+    enums::{ProcessResult, SessionStatus, TransferDirection},
+    types::{Client, Throttle},
+};
+
+const USAGE: &str = "Usage:
+    tucha                                     Start the graphical interface.
+    tucha scrub [--sample N] [--client NAME]  Verify stored files of every (or one) saved client,
+                                              chosen by user id or @username.";
+
+/// Runs a command without the graphical interface and returns the process exit code.
+pub async fn run(args: Vec<String>) -> i32 {
+    match args.first().map(|v| v.as_str()) {
+        Some("scrub") => scrub(&args[1..]).await,
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            0
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            2
+        }
+    }
+}
+
+async fn scrub(args: &[String]) -> i32 {
+    let mut sample_size = None;
+    let mut client_name = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--sample", Some(v)) => match v.parse() {
+                Ok(v) => sample_size = Some(v),
+                Err(_) => {
+                    eprintln!("--sample expects a number of files.");
+                    return 2;
+                }
+            },
+            ("--client", Some(v)) => client_name = Some(v.clone()),
+            _ => {
+                eprintln!("{}", USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let sessions = match Client::connect_to_saved_sessions().await {
+        Ok(ProcessResult::ConnectedToSavedClients(v)) => v,
+        Ok(_) => return 1,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+    let mut clients = Vec::new();
+    for (session_id, result) in sessions {
+        match result {
+            Ok(client) => clients.push((session_id, client)),
+            Err(SessionStatus::Error(err)) => eprintln!("session {}: {}", session_id, err),
+            Err(status) => eprintln!("session {}: {}", session_id, status),
+        }
+    }
+
+    for (session_id, client) in clients {
+        let name = client.display_name();
+        // An account can be chosen by its user id or its @username.
+        if client_name
+            .as_ref()
+            .is_some_and(|v| *v != session_id && *v != name && format!("@{}", v) != name)
+        {
+            continue;
+        }
+
+        let throttle = Throttle::with_saved_limits(TransferDirection::Download);
+        for report in client.scrub(sample_size, throttle).await {
+            match report {
+                Ok(report) => {
+                    println!("{}", report);
+                    for path in &report.mismatched {
+                        println!("    mismatched: {}", path);
+                    }
+                    for (path, error) in &report.unreadable {
+                        println!("    unreadable: {} ({})", path, error);
+                    }
+
+                    if !report.is_healthy() {
+                        exit_code = 1;
+                    }
+                    if let Err(err) = report.append_to_history() {
+                        eprintln!("{}", err);
+                        exit_code = 1;
+                    }
+                }
+                Err(err) => {
+                    eprintln!("{}: {}", name, err);
+                    exit_code = 1;
+                }
+            }
+        }
+    }
+
+    exit_code
+}
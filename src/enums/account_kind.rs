@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// How a saved session was authorized.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum AccountKind {
+    /// A personal account logged in with a phone number or a QR code.
+    #[default]
+    User,
+    /// A bot logged in with its token, it stores files in a chat it administers.
+    Bot,
+}
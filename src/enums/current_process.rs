@@ -1,17 +1,34 @@
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
-use super::ProcessError;
+use crate::types::ProcessError;
 
 pub enum CurrentProcess{
     Idle, 
     Error(ProcessError),
     ConnectingToAllSavedClients,
+    TestingAPIKeys,
+    TestingConnection,
+    ConnectionTested(Duration),
     GettingUploadedFiles,
+    UpdatingDrives,
+    InvitingMember,
+    GettingShareTargets,
+    SharingFile,
     SendingLoginCode,
     LogInWithCode,
-    UploadingFiles,
-    DownloadingFiles,
+    ProvisioningCloud,
+    PreparingQrLogin,
+    LoggingOut,
+    RepairingSession,
     DeletingFiles,
+    CopyingFiles,
+    TransferringFiles(usize, usize),
+    Migrating(usize, usize),
+    Scrubbing,
+    PartiallyFailed {
+        succeeded: usize,
+        errors: Vec<ProcessError>,
+    },
 }
 
 impl Display for CurrentProcess{
@@ -20,12 +37,39 @@ impl Display for CurrentProcess{
             CurrentProcess::Idle => write!(f, "tucha"),
             CurrentProcess::Error(process_error) => write!(f, "{}", process_error),
             CurrentProcess::ConnectingToAllSavedClients => write!(f, "Connecting to all saved clients..."),
+            CurrentProcess::TestingAPIKeys => write!(f, "Testing API keys..."),
+            CurrentProcess::TestingConnection => write!(f, "Testing connection..."),
+            CurrentProcess::ConnectionTested(duration) => write!(
+                f,
+                "Connected to Telegram in {} ms",
+                duration.as_millis()
+            ),
             CurrentProcess::SendingLoginCode => write!(f, "Sending login code..."),
             CurrentProcess::LogInWithCode => write!(f, "Log in..."),
-            CurrentProcess::UploadingFiles => write!(f, "Uploading files..."),
+            CurrentProcess::ProvisioningCloud => write!(f, "Preparing the cloud..."),
+            CurrentProcess::PreparingQrLogin => write!(f, "Preparing QR code..."),
+            CurrentProcess::LoggingOut => write!(f, "Logging out..."),
+            CurrentProcess::RepairingSession => write!(f, "Repairing session..."),
             CurrentProcess::GettingUploadedFiles => write!(f, "Getting uploaded files..."),
-            CurrentProcess::DownloadingFiles => write!(f, "Downloading files..."),
+            CurrentProcess::UpdatingDrives => write!(f, "Updating drives..."),
+            CurrentProcess::InvitingMember => write!(f, "Inviting member..."),
+            CurrentProcess::GettingShareTargets => write!(f, "Getting chats..."),
+            CurrentProcess::SharingFile => write!(f, "Sharing file..."),
             CurrentProcess::DeletingFiles => write!(f, "Deleting files..."),
+            CurrentProcess::CopyingFiles => write!(f, "Copying files..."),
+            CurrentProcess::TransferringFiles(done, total) => {
+                write!(f, "Transferring files... {}/{}", done, total)
+            }
+            CurrentProcess::Migrating(done, total) => {
+                write!(f, "Migrating cloud... {}/{}", done, total)
+            }
+            CurrentProcess::Scrubbing => write!(f, "Scrubbing stored files..."),
+            CurrentProcess::PartiallyFailed { succeeded, errors } => write!(
+                f,
+                "{} succeeded, {} failed",
+                succeeded,
+                errors.len()
+            ),
         }
     }
 }
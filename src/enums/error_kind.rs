@@ -0,0 +1,184 @@
+use std::{error::Error, fmt::Display};
+
+use crate::types::ProcessError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    AccountIsNotReachable,
+    APIKeysAreNotSet,
+    BotCannotCreateChat,
+    CannotChangeDrive,
+    CannotCopyFile,
+    CannotDeleteFile,
+    CannotDownloadMedia,
+    CannotGetDialogs,
+    CannotGetFileName,
+    CannotGetUserData,
+    CannotInviteMember,
+    CannotLoadSessionFile,
+    CannotLogOut,
+    CannotReadFile,
+    CannotReadMessages,
+    CannotReadSessionsDirectory,
+    CannotRemoveSession,
+    CannotSaveAPIKeys,
+    CannotSaveMigration,
+    CannotSaveScrubHistory,
+    CannotSaveSessionInFile,
+    CannotSaveSettings,
+    CannotSaveTransferState,
+    CannotSerializeToString,
+    CannotSetMemberRole,
+    CannotShareFile,
+    CannotTransferFile,
+    CannotUploadFile,
+    CannotWriteFile,
+    ChatIsNone,
+    ChatIsNotGroup,
+    ClientIsNotConnected,
+    CloudChatIsNotFound,
+    CloudGroupIsNotCreated,
+    CurrentClientIsNone,
+    DriveAlreadyExists,
+    DriveIsNotFound,
+    DriveIsNotShareable,
+    HashMismatch,
+    IncompleteClientIsNone,
+    InvalidAPIKeys,
+    InvalidBotToken,
+    InvalidCode,
+    InvalidDriveName,
+    InvalidPassword,
+    InvalidServerAddress,
+    LoginCodeIsNotSended,
+    LoginTokenIsNone,
+    MainDriveCannotBeChanged,
+    MediaMessageIsNotSended,
+    MemberIsNotFound,
+    MessageNotContainsMedia,
+    MessagesNotFound,
+    MTProxyIsNotSupported,
+    OtherSignInError,
+    PasswordRequired,
+    SessionFileIsNotExist,
+    SignUpRequired,
+    StorageChatIsNotChannel,
+    StorageChatIsNotFound,
+    TransferTargetIsSource,
+    UnsupportedProxy,
+    UserIsNone,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::AccountIsNotReachable => write!(
+                f,
+                "The other account cannot be reached. Give it a username or make its phone number visible."
+            ),
+            ErrorKind::APIKeysAreNotSet => write!(
+                f,
+                "API keys are not set. Enter them on the setup screen or set TUCHA_API_ID and TUCHA_API_HASH."
+            ),
+            ErrorKind::BotCannotCreateChat => write!(
+                f,
+                "Bots cannot create chats. Add the bot as an admin to a group or channel and log in with it again."
+            ),
+            ErrorKind::CannotChangeDrive => write!(f, "Cannot rename or delete drive."),
+            ErrorKind::CannotCopyFile => write!(f, "Cannot copy file."),
+            ErrorKind::CannotDeleteFile => write!(f, "Cannot delete file."),
+            ErrorKind::CannotDownloadMedia => write!(f, "Cannot download media from message."),
+            ErrorKind::CannotGetDialogs => write!(f, "Cannot get dialogs."),
+            ErrorKind::CannotGetFileName => write!(f, "Cannot get file name."),
+            ErrorKind::CannotGetUserData => write!(f, "Cannot get user data."),
+            ErrorKind::CannotInviteMember => write!(f, "Cannot invite the member."),
+            ErrorKind::CannotLoadSessionFile => write!(f, "Cannot load session file."),
+            ErrorKind::CannotLogOut => write!(f, "Cannot log out."),
+            ErrorKind::CannotReadFile => write!(f, "Cannot read file."),
+            ErrorKind::CannotReadMessages => write!(f, "Cannot read messages."),
+            ErrorKind::CannotReadSessionsDirectory => {
+                write!(f, "Cannot read directory with sessions files.")
+            }
+            ErrorKind::CannotRemoveSession => write!(f, "Cannot remove session file."),
+            ErrorKind::CannotSaveAPIKeys => write!(f, "Cannot save API keys."),
+            ErrorKind::CannotSaveMigration => write!(f, "Cannot save migration."),
+            ErrorKind::CannotSaveScrubHistory => write!(f, "Cannot save scrub history."),
+            ErrorKind::CannotSaveSessionInFile => write!(f, "Cannot save session in file."),
+            ErrorKind::CannotSaveSettings => write!(f, "Cannot save settings."),
+            ErrorKind::CannotSaveTransferState => write!(f, "Cannot save transfer state."),
+            ErrorKind::CannotSerializeToString => {
+                write!(f, "Cannot serialize file metadata to string.")
+            }
+            ErrorKind::CannotSetMemberRole => write!(f, "Cannot set the role of the member."),
+            ErrorKind::CannotShareFile => write!(f, "Cannot share file."),
+            ErrorKind::CannotTransferFile => write!(f, "Cannot transfer file."),
+            ErrorKind::CannotUploadFile => write!(f, "Cannot upload file."),
+            ErrorKind::CannotWriteFile => write!(f, "Cannot write file."),
+            ErrorKind::ChatIsNone => write!(f, "Chat is None."),
+            ErrorKind::ChatIsNotGroup => {
+                write!(f, "Files can only be stored in a group or a channel.")
+            }
+            ErrorKind::ClientIsNotConnected => write!(f, "Client is not connected."),
+            ErrorKind::CloudChatIsNotFound => write!(f, "Cloud chat is not found."),
+            ErrorKind::CloudGroupIsNotCreated => write!(f, "Cloud gropup is not created."),
+            ErrorKind::CurrentClientIsNone => write!(f, "Current client is None."),
+            ErrorKind::DriveAlreadyExists => write!(f, "A drive with this name already exists."),
+            ErrorKind::DriveIsNotFound => write!(f, "Drive is not found."),
+            ErrorKind::DriveIsNotShareable => write!(
+                f,
+                "Only drives stored in a supergroup or channel can be shared."
+            ),
+            ErrorKind::HashMismatch => {
+                write!(f, "Downloaded file does not match the uploaded one.")
+            }
+            ErrorKind::IncompleteClientIsNone => {
+                write!(f, "Incomplete telegram client is None.")
+            }
+            ErrorKind::InvalidAPIKeys => write!(f, "Invalid API ID or API hash."),
+            ErrorKind::InvalidBotToken => write!(f, "Invalid bot token."),
+            ErrorKind::InvalidCode => write!(f, "Invalid code."),
+            ErrorKind::InvalidDriveName => write!(f, "Drive name cannot be empty."),
+            ErrorKind::InvalidPassword => write!(f, "Invalid password."),
+            ErrorKind::InvalidServerAddress => {
+                write!(f, "Invalid server address, expected ip:port.")
+            }
+            ErrorKind::LoginCodeIsNotSended => write!(f, "Login code is not sended."),
+            ErrorKind::LoginTokenIsNone => write!(f, "Login token is None."),
+            ErrorKind::MainDriveCannotBeChanged => {
+                write!(f, "The main drive cannot be renamed or deleted.")
+            }
+            ErrorKind::MediaMessageIsNotSended => write!(f, "Media message is not sended."),
+            ErrorKind::MemberIsNotFound => write!(f, "No Telegram user has this username."),
+            ErrorKind::MessageNotContainsMedia => write!(f, "Message not contains media."),
+            ErrorKind::MessagesNotFound => write!(f, "Message not found."),
+            ErrorKind::MTProxyIsNotSupported => write!(
+                f,
+                "MTProxy is not supported by grammers 0.6, use a SOCKS5 proxy (socks5://host:port)."
+            ),
+            ErrorKind::OtherSignInError => write!(f, "Other sign in error."),
+            ErrorKind::PasswordRequired => write!(f, "Password required."),
+            ErrorKind::SessionFileIsNotExist => write!(f, "Session file is not exist."),
+            ErrorKind::SignUpRequired => write!(f, "Sign up required."),
+            ErrorKind::StorageChatIsNotChannel => write!(
+                f,
+                "Bots can only store files in a supergroup or a channel."
+            ),
+            ErrorKind::StorageChatIsNotFound => write!(
+                f,
+                "Storage chat is not found. Check that the bot is an admin of it."
+            ),
+            ErrorKind::TransferTargetIsSource => write!(f, "Files are already in this drive."),
+            ErrorKind::UnsupportedProxy => write!(
+                f,
+                "Unsupported proxy, expected socks5://[user:password@]host:port."
+            ),
+            ErrorKind::UserIsNone => write!(f, "User is None."),
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn with_source(self, source: impl Error + Send + Sync + 'static) -> ProcessError {
+        ProcessError::from(self).with_source(source)
+    }
+}
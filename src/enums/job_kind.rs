@@ -0,0 +1,56 @@
+use std::{fmt::Display, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{File, Path};
+
+use super::TransferDirection;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum JobKind {
+    Upload { file: PathBuf, path: Path },
+    Download(File),
+    Verify(File),
+}
+
+impl JobKind {
+    pub fn direction(&self) -> TransferDirection {
+        match self {
+            JobKind::Upload { .. } => TransferDirection::Upload,
+            JobKind::Download(_) | JobKind::Verify(_) => TransferDirection::Download,
+        }
+    }
+
+    /// Stable identifier of the transfer, used to find its saved progress. Only
+    /// fields that never change are used, so adding fields to `File` keeps the key.
+    pub fn transfer_key(&self, client_name: &str, drive: &str) -> String {
+        let target = match self {
+            JobKind::Upload { file, path } => {
+                format!("upload\n{}\n{}", file.display(), path.path())
+            }
+            JobKind::Download(file) => format!("download\n{}", file.message_id),
+            JobKind::Verify(file) => format!("verify\n{}", file.message_id),
+        };
+        format!(
+            "{:x}",
+            md5::compute(format!("{}\n{}\n{}", client_name, drive, target))
+        )
+    }
+}
+
+impl Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobKind::Upload { file, path } => write!(
+                f,
+                "Upload {} to {}",
+                file.file_name()
+                    .map(|v| v.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                path.path()
+            ),
+            JobKind::Download(file) => write!(f, "Download {}", file.path.path()),
+            JobKind::Verify(file) => write!(f, "Verify {}", file.path.path()),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::{fmt::Display, time::Instant};
+
+use crate::types::ProcessError;
+
+use super::RetryReason;
+
+#[derive(Debug, Clone)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    /// Running, but sleeping before retrying a transient error.
+    Waiting {
+        until: Instant,
+        reason: RetryReason,
+    },
+    Paused,
+    Done,
+    Failed(ProcessError),
+}
+
+impl JobStatus {
+    /// Whether the job occupies one of the parallel transfer slots.
+    pub fn is_active(&self) -> bool {
+        matches!(self, JobStatus::Running | JobStatus::Waiting { .. })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed(_))
+    }
+}
+
+impl Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobStatus::Queued => write!(f, "Queued"),
+            JobStatus::Running => write!(f, "Running"),
+            JobStatus::Waiting { until, reason } => {
+                let seconds = until.saturating_duration_since(Instant::now()).as_secs();
+                match seconds {
+                    0 => write!(f, "Retrying after {}...", reason),
+                    _ => write!(f, "Waiting {} s due to {}", seconds, reason),
+                }
+            }
+            JobStatus::Paused => write!(f, "Paused"),
+            JobStatus::Done => write!(f, "Done"),
+            JobStatus::Failed(process_error) => write!(f, "Failed: {}", process_error),
+        }
+    }
+}
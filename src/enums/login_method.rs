@@ -0,0 +1,6 @@
+#[derive(PartialEq, Clone, Copy)]
+pub enum LoginMethod {
+    PhoneNumber,
+    QrCode,
+    BotToken,
+}
@@ -0,0 +1,35 @@
+use std::fmt::Display;
+
+/// What a member of a shared drive may do, mapped onto Telegram admin rights.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MemberRole {
+    /// Can list and download files, but cannot post to the chat.
+    #[default]
+    Viewer,
+    /// Can also upload files and move them.
+    Editor,
+    /// Can also delete files and manage the other members.
+    Owner,
+}
+
+impl MemberRole {
+    pub const ALL: [MemberRole; 3] = [MemberRole::Viewer, MemberRole::Editor, MemberRole::Owner];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            MemberRole::Viewer => "Read only",
+            MemberRole::Editor => "Upload and move files",
+            MemberRole::Owner => "Upload, move and delete files, manage members",
+        }
+    }
+}
+
+impl Display for MemberRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemberRole::Viewer => write!(f, "Viewer"),
+            MemberRole::Editor => write!(f, "Editor"),
+            MemberRole::Owner => write!(f, "Owner"),
+        }
+    }
+}
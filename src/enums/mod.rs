@@ -1,9 +1,29 @@
-mod process_error;
+mod account_kind;
+mod error_kind;
 mod current_process;
+mod job_kind;
+mod job_status;
+mod login_method;
+mod member_role;
 mod new_process;
 mod process_result;
+mod retry_reason;
+mod session_status;
+mod theme;
+mod transfer_direction;
+mod transfer_event;
 
-pub use process_error::ProcessError;
+pub use account_kind::AccountKind;
+pub use error_kind::ErrorKind;
 pub use current_process::CurrentProcess;
+pub use job_kind::JobKind;
+pub use job_status::JobStatus;
+pub use login_method::LoginMethod;
+pub use member_role::MemberRole;
 pub use new_process::NewProcess;
 pub use process_result::ProcessResult;
+pub use retry_reason::RetryReason;
+pub use session_status::SessionStatus;
+pub use theme::Theme;
+pub use transfer_direction::TransferDirection;
+pub use transfer_event::TransferEvent;
@@ -1,25 +1,64 @@
-use crate::types::Client;
+use crate::types::{Client, File, Path, ShareTarget, TransferState};
 use crate::ui::window::Window;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Instant};
+use tracing::debug;
 
 use crate::enums::CurrentProcess;
 use crate::utils;
 
-use super::ProcessError;
+use super::JobKind;
+use super::JobStatus;
+use super::MemberRole;
+use super::ErrorKind;
 use super::ProcessResult;
+use super::TransferDirection;
 
+#[derive(Debug)]
 pub enum NewProcess {
     ConnectToAllSavedClients,
+    SaveAPIKeys,
+    TestConnection,
+    LogOut(String),
+    RemoveSession(String),
+    ReconnectSession(String),
+    RepairCloudChat(String),
     GetUploadedFiles,
+    CreateDrive(String),
+    RenameDrive(String, String),
+    DeleteDrive(String),
+    RecreateDrive(String),
+    RebindDrive(String, String, String),
+    AddSharedDrive(String, String),
+    InviteMember(String, MemberRole),
+    GetShareTargets,
+    ShareFile(File, ShareTarget),
     SendLoginCode,
     SingIn,
+    StartQrLogin,
+    PollQrLogin,
+    BotSignIn,
+    ProvisionCloud(bool),
     UploadFiles(Vec<PathBuf>),
-    DownloadFiles(Vec<i32>),
-    DeleteFiles(Vec<i32>),
+    DownloadFiles(Vec<File>),
+    VerifyFiles(Vec<File>),
+    DeleteFiles(Vec<File>),
+    CopyFiles(Vec<File>, Path, Path),
+    MigrateCloud(String, String),
+    TransferFiles {
+        files: Vec<File>,
+        source: Path,
+        destination: Path,
+        target_client: String,
+        target_drive: String,
+        is_move: bool,
+    },
+    RunJob(usize),
+    Scrub(Option<usize>),
 }
 
 impl NewProcess {
     pub fn start(self, window: &mut Window) {
+        debug!(process = ?self, "starting process");
         match self {
             NewProcess::ConnectToAllSavedClients => {
                 window.current_process = CurrentProcess::ConnectingToAllSavedClients;
@@ -29,6 +68,74 @@ impl NewProcess {
                     utils::send_result(sender, Client::connect_to_saved_sessions().await);
                 });
             }
+            NewProcess::SaveAPIKeys => {
+                let sender = window.sender.clone();
+                let api_keys = match window.setup_tab.api_keys() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+                window.current_process = CurrentProcess::TestingAPIKeys;
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, Client::test_api_keys(api_keys).await);
+                });
+            }
+            NewProcess::TestConnection => {
+                window.current_process = CurrentProcess::TestingConnection;
+                let sender = window.sender.clone();
+                let proxy_url = window.settings.proxy.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, Client::test_connection(proxy_url).await);
+                });
+            }
+            NewProcess::LogOut(client_name) => {
+                window.current_process = CurrentProcess::LoggingOut;
+
+                let sender = window.sender.clone();
+                let client = match window.clients.get(&client_name) {
+                    Some(v) => v.clone(),
+                    None => {
+                        let _ = sender.send(ProcessResult::Error(ErrorKind::CurrentClientIsNone.into()));
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.log_out().await);
+                });
+            }
+            NewProcess::RemoveSession(session_id) => {
+                if let Err(err) = Client::remove_session(&session_id) {
+                    window.current_process = CurrentProcess::Error(err);
+                    return;
+                }
+                window.forget_session(&session_id);
+                window.remove_client(&session_id);
+            }
+            NewProcess::ReconnectSession(session_id) => {
+                window.current_process = CurrentProcess::RepairingSession;
+                let sender = window.sender.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, Client::reconnect_saved_session(session_id).await);
+                });
+            }
+            NewProcess::RepairCloudChat(session_id) => {
+                let client = match window.session_client(&session_id) {
+                    Some(v) => v,
+                    None => return,
+                };
+                window.current_process = CurrentProcess::RepairingSession;
+                let sender = window.sender.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.repair_cloud_chat().await);
+                });
+            }
             NewProcess::SendLoginCode => {
                 window.current_process = CurrentProcess::SendingLoginCode;
 
@@ -39,6 +146,18 @@ impl NewProcess {
                     utils::send_result(sender, Client::send_login_code(phone_number).await);
                 });
             }
+            NewProcess::ProvisionCloud(is_reused) => {
+                let client = match window.new_session_tab.existing_cloud.take() {
+                    Some(v) => v,
+                    None => return,
+                };
+                window.current_process = CurrentProcess::ProvisioningCloud;
+                let sender = window.sender.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.provision_cloud(is_reused).await);
+                });
+            }
             NewProcess::SingIn => {
                 window.current_process = CurrentProcess::LogInWithCode;
 
@@ -47,7 +166,7 @@ impl NewProcess {
                     Some(v) => v,
                     None => {
                         let _sender_result =
-                            sender.send(ProcessResult::Error(ProcessError::IncompleteClientIsNone));
+                            sender.send(ProcessResult::Error(ErrorKind::IncompleteClientIsNone.into()));
                         return;
                     }
                 };
@@ -56,7 +175,7 @@ impl NewProcess {
                     Some(v) => v,
                     None => {
                         let _sender_result =
-                            sender.send(ProcessResult::Error(ProcessError::LoginTokenIsNone));
+                            sender.send(ProcessResult::Error(ErrorKind::LoginTokenIsNone.into()));
                         return;
                     }
                 };
@@ -71,78 +190,422 @@ impl NewProcess {
                     );
                 });
             }
+            NewProcess::StartQrLogin => {
+                window.current_process = CurrentProcess::PreparingQrLogin;
+                let sender = window.sender.clone();
+
+                tokio::spawn(async move {
+                    let _ = sender.send(match Client::start_qr_login().await {
+                        Ok(v) => v,
+                        Err(err) => ProcessResult::QrLoginFailed(err),
+                    });
+                });
+            }
+            NewProcess::BotSignIn => {
+                window.current_process = CurrentProcess::LogInWithCode;
+                let sender = window.sender.clone();
+                let bot_token = window.new_session_tab.bot_token.clone();
+                let bot_chat = window.new_session_tab.bot_chat.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, Client::bot_sign_in(bot_token, bot_chat).await);
+                });
+            }
+            NewProcess::PollQrLogin => {
+                let new_session_tab = &mut window.new_session_tab;
+                let client = match new_session_tab.incomplete_client.clone() {
+                    Some(v) => v,
+                    None => return,
+                };
+                let user_password = if new_session_tab.is_qr_password_required {
+                    window.current_process = CurrentProcess::LogInWithCode;
+                    new_session_tab.user_password.clone()
+                } else {
+                    String::new()
+                };
+                new_session_tab.is_polling_qr = true;
+                new_session_tab.last_qr_poll = Some(Instant::now());
+                let sender = window.sender.clone();
+
+                tokio::spawn(async move {
+                    let _ = sender.send(match client.poll_qr_login(user_password).await {
+                        Ok(v) => v,
+                        Err(err) => ProcessResult::QrLoginFailed(err),
+                    });
+                });
+            }
             NewProcess::UploadFiles(transferred_files) => {
-                window.current_process = CurrentProcess::UploadingFiles;
+                let path = window.cloud_tab.current_path.clone();
+                let drive = window.current_drive();
+
+                for file in transferred_files {
+                    window.transfer_queue.push(
+                        window.current_client.clone(),
+                        drive.clone(),
+                        JobKind::Upload {
+                            file,
+                            path: path.clone(),
+                        },
+                    );
+                }
+            }
+            NewProcess::GetUploadedFiles => {
+                window.current_process = CurrentProcess::GettingUploadedFiles;
 
                 let sender = window.sender.clone();
-                let transferred_files = transferred_files.clone();
                 let client = match window.get_current_client() {
                     Ok(v) => v,
                     Err(err) => {
-                        let _ = sender.send(err.to_process_result());
+                        let _ = sender.send(err.into_process_result());
                         return;
                     }
                 };
-                let path = window.cloud_tab.current_path.clone();
 
                 tokio::spawn(async move {
-                    utils::send_result(
-                        sender,
-                        client.upload_files(transferred_files.clone(), path).await,
-                    );
+                    utils::send_result(sender, client.get_uploaded_files().await);
                 });
             }
-            NewProcess::GetUploadedFiles => {
-                window.current_process = CurrentProcess::GettingUploadedFiles;
+            NewProcess::CreateDrive(name) => {
+                window.current_process = CurrentProcess::UpdatingDrives;
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
 
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.create_drive(name).await);
+                });
+            }
+            NewProcess::RenameDrive(name, new_name) => {
+                window.current_process = CurrentProcess::UpdatingDrives;
                 let sender = window.sender.clone();
                 let client = match window.get_current_client() {
                     Ok(v) => v,
                     Err(err) => {
-                        let _ = sender.send(err.to_process_result());
+                        let _ = sender.send(err.into_process_result());
                         return;
                     }
                 };
 
                 tokio::spawn(async move {
-                    utils::send_result(sender, client.get_uploaded_files().await);
+                    utils::send_result(sender, client.rename_drive(name, new_name).await);
                 });
             }
-            NewProcess::DownloadFiles(message_ids) => {
-                window.current_process = CurrentProcess::DownloadingFiles;
+            NewProcess::DeleteDrive(name) => {
+                window.current_process = CurrentProcess::UpdatingDrives;
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
 
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.delete_drive(name).await);
+                });
+            }
+            NewProcess::RecreateDrive(name) => {
+                window.current_process = CurrentProcess::UpdatingDrives;
                 let sender = window.sender.clone();
-                let client = match window.clients.get(&window.current_client) {
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.recreate_drive(name).await);
+                });
+            }
+            NewProcess::RebindDrive(session_id, name, address) => {
+                let client = match window.session_client(&session_id) {
                     Some(v) => v,
+                    None => return,
+                };
+                window.current_process = CurrentProcess::UpdatingDrives;
+                let sender = window.sender.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.rebind_drive(name, address).await);
+                });
+            }
+            NewProcess::AddSharedDrive(name, address) => {
+                window.current_process = CurrentProcess::UpdatingDrives;
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.add_shared_drive(name, address).await);
+                });
+            }
+            NewProcess::InviteMember(username, role) => {
+                window.current_process = CurrentProcess::InvitingMember;
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+                let drive = client.drive.clone();
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.invite_member(drive, username, role).await);
+                });
+            }
+            NewProcess::GetShareTargets => {
+                window.current_process = CurrentProcess::GettingShareTargets;
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.get_share_targets().await);
+                });
+            }
+            NewProcess::ShareFile(file, target) => {
+                window.current_process = CurrentProcess::SharingFile;
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.share_file(file, target).await);
+                });
+            }
+            NewProcess::DownloadFiles(files) => {
+                let drive = window.current_drive();
+                for file in files {
+                    window.transfer_queue.push(
+                        window.current_client.clone(),
+                        drive.clone(),
+                        JobKind::Download(file),
+                    );
+                }
+            }
+            NewProcess::VerifyFiles(files) => {
+                let drive = window.current_drive();
+                for file in files {
+                    window.transfer_queue.push(
+                        window.current_client.clone(),
+                        drive.clone(),
+                        JobKind::Verify(file),
+                    );
+                }
+            }
+            NewProcess::DeleteFiles(files) => {
+                window.current_process = CurrentProcess::DeletingFiles;
+
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(sender, client.delete_files(files).await);
+                });
+            }
+            NewProcess::CopyFiles(files, source, destination) => {
+                window.current_process = CurrentProcess::CopyingFiles;
+
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+
+                tokio::spawn(async move {
+                    utils::send_result(
+                        sender,
+                        client.copy_files(files, source, destination).await,
+                    );
+                });
+            }
+            NewProcess::TransferFiles {
+                files,
+                source,
+                destination,
+                target_client,
+                target_drive,
+                is_move,
+            } => {
+                window.current_process = CurrentProcess::TransferringFiles(0, files.len());
+
+                let sender = window.sender.clone();
+                let client = match window.get_current_client() {
+                    Ok(v) => v,
+                    Err(err) => {
+                        let _ = sender.send(err.into_process_result());
+                        return;
+                    }
+                };
+                let mut target = match window.clients.get(&target_client) {
+                    Some(v) => v.clone(),
                     None => {
-                        let _sender_result =
-                            sender.send(ProcessResult::Error(ProcessError::CurrentClientIsNone));
+                        let _ = sender.send(ProcessResult::Error(
+                            ErrorKind::ClientIsNotConnected.into(),
+                        ));
                         return;
                     }
+                };
+                if !target.select_drive(&target_drive) {
+                    let _ = sender.send(ProcessResult::Error(ErrorKind::DriveIsNotFound.into()));
+                    return;
                 }
-                .clone();
-                let message_ids = message_ids.clone();
 
                 tokio::spawn(async move {
-                    utils::send_result(sender, client.download_files(message_ids.clone()).await);
+                    let progress_sender = sender.clone();
+                    let on_progress = move |done, total| {
+                        let _ = progress_sender.send(ProcessResult::TransferProgress(done, total));
+                    };
+                    let result = client
+                        .transfer_files(target, files, source, destination, is_move, on_progress)
+                        .await;
+                    utils::send_result(sender, result);
                 });
             }
-            NewProcess::DeleteFiles(message_ids) => {
-                window.current_process = CurrentProcess::DeletingFiles;
+            NewProcess::MigrateCloud(source_client, target_client) => {
+                let sender = window.sender.clone();
+                let (client, target) = match (
+                    window.clients.get(&source_client),
+                    window.clients.get(&target_client),
+                ) {
+                    (Some(client), Some(target)) => (client.clone(), target.clone()),
+                    _ => {
+                        let _ = sender.send(ProcessResult::Error(
+                            ErrorKind::ClientIsNotConnected.into(),
+                        ));
+                        return;
+                    }
+                };
+                window.current_process = CurrentProcess::Migrating(0, 0);
+                window.accounts_tab.migration_report = None;
+
+                tokio::spawn(async move {
+                    let progress_sender = sender.clone();
+                    let on_progress = move |done, total| {
+                        let _ = progress_sender.send(ProcessResult::MigrationProgress(done, total));
+                    };
+                    utils::send_result(sender, client.migrate(target, on_progress).await);
+                });
+            }
+            NewProcess::Scrub(sample_size) => {
+                window.current_process = CurrentProcess::Scrubbing;
 
                 let sender = window.sender.clone();
                 let client = match window.get_current_client() {
                     Ok(v) => v,
                     Err(err) => {
-                        let _ = sender.send(err.to_process_result());
+                        let _ = sender.send(err.into_process_result());
                         return;
                     }
                 };
-                let message_ids = message_ids.clone();
+                let throttle = window
+                    .transfer_queue
+                    .unqueued_throttle(TransferDirection::Download);
 
                 tokio::spawn(async move {
-                    utils::send_result(sender, client.delete_files(message_ids).await);
+                    utils::send_result(
+                        sender,
+                        Ok(ProcessResult::ScrubFinished(
+                            client.scrub(sample_size, throttle).await,
+                        )),
+                    );
+                });
+            }
+            NewProcess::RunJob(job_id) => {
+                let sender = window.sender.clone();
+                let throttle = match window.transfer_queue.throttle(job_id) {
+                    Some(v) => v,
+                    None => return,
+                };
+                let job = match window.transfer_queue.get_mut(job_id) {
+                    Some(v) => v,
+                    None => return,
+                };
+                let mut client = match window.clients.get(&job.client_name) {
+                    Some(v) => v.clone(),
+                    None => {
+                        job.set_finished(JobStatus::Failed(ErrorKind::CurrentClientIsNone.into()));
+                        return;
+                    }
+                };
+                // The job keeps its drive even if another one is selected meanwhile.
+                if !client.select_drive(&job.drive) {
+                    job.set_finished(JobStatus::Failed(ErrorKind::DriveIsNotFound.into()));
+                    return;
+                }
+                let kind = job.kind.clone();
+                let settings = window.settings.clone();
+                let (operation, path) = match &kind {
+                    JobKind::Upload { file, .. } => ("Upload", file.display().to_string()),
+                    JobKind::Download(file) => ("Download", file.path.path()),
+                    JobKind::Verify(file) => ("Verify", file.path.path()),
+                };
+                let state = TransferState::load_or_new(&job.client_name, &job.drive, &kind);
+
+                let join_handle = tokio::spawn(async move {
+                    let event_sender = sender.clone();
+                    let on_event = move |event| {
+                        let _ = event_sender.send(ProcessResult::JobEvent(job_id, event));
+                    };
+
+                    let result = match kind {
+                        JobKind::Upload { file, path } => {
+                            client
+                                .upload_file(file, path, state, throttle, on_event)
+                                .await
+                        }
+                        JobKind::Download(file) => {
+                            client
+                                .download_file(file, settings, state, throttle, on_event)
+                                .await
+                        }
+                        JobKind::Verify(file) => {
+                            client.verify_file(file, state, throttle, on_event).await
+                        }
+                    };
+                    let _ = sender.send(match result {
+                        Ok(_) => ProcessResult::JobDone(job_id),
+                        Err(err) => ProcessResult::JobFailed(
+                            job_id,
+                            err.with_operation(operation).with_path(path),
+                        ),
+                    });
                 });
+                job.set_running(join_handle.abort_handle());
             }
         }
     }
@@ -1,36 +1,89 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{sync::Arc, time::Duration};
 
 use grammers_client::types::LoginToken;
+use tracing::{error, info, warn};
 
 use crate::{
-    types::{Client, Dir, File},
-    ui::{tab::Tab, window::Window},
+    types::{
+        Client, Dir, File, Migration, Path, ProcessError, QrLogin, ScrubReport, ShareTarget,
+        TransferState,
+    },
+    ui::{
+        tab::{NewSession, Tab},
+        window::Window,
+    },
 };
 
-use super::{CurrentProcess, NewProcess, ProcessError};
+use super::{
+    CurrentProcess, JobKind, JobStatus, MemberRole, NewProcess, SessionStatus, TransferEvent,
+};
 
 pub enum ProcessResult {
     Error(ProcessError),
-    ConnectedToSavedClients(BTreeMap<String, Client>),
+    APIKeysSaved,
+    ConnectionTested(Duration),
+    ConnectedToSavedClients(Vec<(String, Result<Client, SessionStatus>)>),
+    SessionConnected(String, Result<Client, SessionStatus>),
     LoginCodeSended(LoginToken, Client),
     LoggedIn(Client, String),
-    FilesUploaded,
-    UploadedFilesReceived(String, Vec<File>),
-    FilesDownloaded,
-    FilesDeleted,
+    ExistingCloudFound(Client),
+    LoggedOut(String),
+    QrLoginTokenReceived(Client, QrLogin),
+    QrPasswordRequired(Client),
+    QrLoginFailed(ProcessError),
+    UploadedFilesReceived(String, String, Vec<File>),
+    DrivesUpdated(String, Client),
+    MemberInvited(String, MemberRole),
+    ShareTargetsReceived(String, Vec<ShareTarget>),
+    FileShared(String),
+    FilesDeleted(Vec<(File, Result<(), ProcessError>)>),
+    FilesCopied(Vec<(File, Result<(), ProcessError>)>),
+    TransferProgress(usize, usize),
+    FilesTransferred(String, Vec<(File, Result<(), ProcessError>)>),
+    MigrationProgress(usize, usize),
+    MigrationFinished(Migration),
+    ScrubFinished(Vec<Result<ScrubReport, ProcessError>>),
+    JobEvent(usize, TransferEvent),
+    JobDone(usize),
+    JobFailed(usize, ProcessError),
 }
 
 impl ProcessResult {
     pub fn check_result(window: &mut Window) {
-        let receiver = &mut window.receiver;
-        if let Ok(process_result) = receiver.try_recv() {
+        while let Ok(process_result) = window.receiver.try_recv() {
             match process_result {
-                ProcessResult::ConnectedToSavedClients(clients) => {
+                ProcessResult::ConnectedToSavedClients(sessions) => {
                     window.current_process = CurrentProcess::Idle;
 
-                    if let Some(first_client) = &clients.clone().first_key_value() {
-                        window.clients = clients;
-                        window.current_client = first_client.0.to_string();
+                    for (session_id, result) in sessions {
+                        window.add_session(session_id, result);
+                    }
+                    info!(
+                        connected = window.clients.len(),
+                        total = window.session_statuses.len(),
+                        "loaded saved sessions"
+                    );
+                    if window.clients.is_empty() && !window.session_statuses.is_empty() {
+                        window.tab = Tab::Accounts;
+                    }
+
+                    if let Some(first_client) = window.clients.keys().next().cloned() {
+                        window.current_client =
+                            window.default_client().unwrap_or(first_client);
+
+                        for state in TransferState::load_all() {
+                            if window.clients.contains_key(&state.client_name)
+                                && !window
+                                    .transfer_queue
+                                    .contains(&state.client_name, &state.drive, &state.kind)
+                            {
+                                window.transfer_queue.push(
+                                    state.client_name,
+                                    state.drive,
+                                    state.kind,
+                                );
+                            }
+                        }
 
                         NewProcess::GetUploadedFiles.start(window);
                     }
@@ -41,21 +94,89 @@ impl ProcessResult {
                     window.new_session_tab.is_code_received = true;
                     window.new_session_tab.incomplete_client = Some(client);
                 }
-                ProcessResult::LoggedIn(client, client_name) => {
+                ProcessResult::LoggedIn(client, session_id) => {
                     window.current_process = CurrentProcess::Idle;
                     window.tab = Tab::Cloud;
+                    window.new_session_tab = NewSession::new();
 
-                    window.current_client = client_name.clone();
-
-                    window.clients.insert(client_name, client);
+                    window.current_client = session_id.clone();
+                    window.add_session(session_id, Ok(client));
                 }
-                ProcessResult::FilesUploaded => {
+                ProcessResult::ExistingCloudFound(client) => {
                     window.current_process = CurrentProcess::Idle;
+                    window.new_session_tab.existing_cloud = Some(client);
+                }
+                ProcessResult::QrLoginTokenReceived(client, qr_login) => {
+                    if matches!(window.current_process, CurrentProcess::PreparingQrLogin) {
+                        window.current_process = CurrentProcess::Idle;
+                    }
+                    let new_session_tab = &mut window.new_session_tab;
+                    new_session_tab.incomplete_client = Some(client);
+                    new_session_tab.qr_login = Some(qr_login);
+                    new_session_tab.is_polling_qr = false;
+                }
+                ProcessResult::QrPasswordRequired(client) => {
+                    window.current_process = CurrentProcess::Idle;
+                    let new_session_tab = &mut window.new_session_tab;
+                    new_session_tab.incomplete_client = Some(client);
+                    new_session_tab.is_qr_password_required = true;
+                    new_session_tab.is_polling_qr = false;
+                }
+                ProcessResult::QrLoginFailed(error) => {
+                    error!("{}", error);
+                    window.current_process = CurrentProcess::Error(error);
+                    let new_session_tab = &mut window.new_session_tab;
+                    new_session_tab.incomplete_client = None;
+                    new_session_tab.qr_login = None;
+                    new_session_tab.is_qr_password_required = false;
+                    new_session_tab.is_polling_qr = false;
+                }
+                ProcessResult::SessionConnected(session_id, mut result) => {
+                    window.current_process = CurrentProcess::Idle;
+                    match (&mut result, window.clients.get(&session_id)) {
+                        // A reconnected client keeps the drive it had, the files shown
+                        // are reloaded in case that drive is gone.
+                        (Ok(client), Some(old_client)) => {
+                            client.select_drive(&old_client.drive);
+                            window.cloud_tab.clients_roots.remove(&session_id);
+                        }
+                        (Err(_), Some(_)) => window.remove_client(&session_id),
+                        _ => {}
+                    }
+                    if result.is_ok() {
+                        window.transfer_queue.resume_after_reconnect(&session_id);
+                    }
+                    let is_current = window.current_client == session_id;
+                    window.add_session(session_id, result);
+                    if is_current {
+                        NewProcess::GetUploadedFiles.start(window);
+                    }
 
-                    NewProcess::GetUploadedFiles.start(window);
+                    if window.current_client.is_empty() {
+                        if let Some(first_client) = window.clients.keys().next().cloned() {
+                            window.current_client = first_client;
+                            NewProcess::GetUploadedFiles.start(window);
+                        }
+                    }
                 }
-                ProcessResult::UploadedFilesReceived(client_name, files) => {
+                ProcessResult::LoggedOut(session_id) => {
+                    info!(session = %session_id, "logged out");
                     window.current_process = CurrentProcess::Idle;
+                    window.forget_session(&session_id);
+                    window.remove_client(&session_id);
+                }
+                ProcessResult::UploadedFilesReceived(client_name, drive, files) => {
+                    if matches!(window.current_process, CurrentProcess::GettingUploadedFiles) {
+                        window.current_process = CurrentProcess::Idle;
+                    }
+                    // Files of a drive that is no longer selected would show up in the wrong one.
+                    if window
+                        .clients
+                        .get(&client_name)
+                        .is_some_and(|client| client.drive != drive)
+                    {
+                        continue;
+                    }
                     let mut root = Dir::root();
 
                     for file in files {
@@ -72,18 +193,149 @@ impl ProcessResult {
 
                     window.cloud_tab.clients_roots.insert(client_name, root);
                 }
-                ProcessResult::FilesDownloaded => {
+                ProcessResult::DrivesUpdated(session_id, client) => {
+                    info!(session = %session_id, drive = %client.drive, "drives updated");
+                    window.current_process = CurrentProcess::Idle;
+                    let result = match client.chat {
+                        Some(_) => Ok(client),
+                        None => Err(SessionStatus::CloudChatMissing(Box::new(client))),
+                    };
+                    window.add_session(session_id.clone(), result);
+
+                    if window.current_client.is_empty() && window.clients.contains_key(&session_id)
+                    {
+                        window.current_client = session_id.clone();
+                    }
+                    if window.current_client == session_id {
+                        window.cloud_tab.clients_roots.remove(&session_id);
+                        window.cloud_tab.current_path = Path::default();
+                        NewProcess::GetUploadedFiles.start(window);
+                    }
+                }
+                ProcessResult::MemberInvited(username, role) => {
+                    info!(%username, %role, "member invited");
+                    window.current_process = CurrentProcess::Idle;
+                }
+                ProcessResult::ShareTargetsReceived(client_name, targets) => {
                     window.current_process = CurrentProcess::Idle;
+                    window.cloud_tab.share_targets.insert(client_name, targets);
+                }
+                ProcessResult::FileShared(target) => {
+                    info!(%target, "file shared");
+                    window.current_process = CurrentProcess::Idle;
+                }
+                ProcessResult::ConnectionTested(duration) => {
+                    info!(?duration, "connection test passed");
+                    window.current_process = CurrentProcess::ConnectionTested(duration);
+                }
+                ProcessResult::APIKeysSaved => {
+                    info!("API keys saved");
+                    window.current_process = CurrentProcess::Idle;
+                    window.tab = Tab::Cloud;
+                    window.setup_tab.is_changing = false;
+
+                    NewProcess::ConnectToAllSavedClients.start(window);
                 }
                 ProcessResult::Error(error) => {
+                    error!("{}", error);
                     window.current_process = CurrentProcess::Error(error);
                 }
-                ProcessResult::FilesDeleted => {
+                ProcessResult::TransferProgress(done, total) => {
+                    window.current_process = CurrentProcess::TransferringFiles(done, total);
+                }
+                ProcessResult::FilesTransferred(target, results) => {
+                    info!(%target, count = results.len(), "files transferred");
                     window.current_process = CurrentProcess::Idle;
+                    window.cloud_tab.clients_roots.remove(&target);
+                    Self::files_changed(window, results);
+                }
+                ProcessResult::MigrationProgress(done, total) => {
+                    window.current_process = CurrentProcess::Migrating(done, total);
+                }
+                ProcessResult::MigrationFinished(migration) => {
+                    info!(
+                        target = %migration.target_client,
+                        verified = migration.is_verified(),
+                        "migration finished"
+                    );
+                    window.current_process = CurrentProcess::Idle;
+                    window.cloud_tab.clients_roots.remove(&migration.target_client);
+                    window.accounts_tab.migration_report = Some(migration);
+                }
+                ProcessResult::FilesDeleted(results) | ProcessResult::FilesCopied(results) => {
+                    Self::files_changed(window, results);
+                }
+                ProcessResult::ScrubFinished(reports) => {
+                    window.current_process = CurrentProcess::Idle;
+                    for report in reports {
+                        match report.and_then(|v| v.append_to_history()) {
+                            Ok(history) => window.transfers_tab.scrub_history = history,
+                            Err(err) => {
+                                error!("{}", err);
+                                window.current_process = CurrentProcess::Error(err);
+                            }
+                        }
+                    }
+                }
+                ProcessResult::JobEvent(job_id, event) => {
+                    if let Some(job) = window.transfer_queue.get_mut(job_id) {
+                        if job.status.is_active() {
+                            job.apply_event(event);
+                        }
+                    }
+                }
+                ProcessResult::JobDone(job_id) => {
+                    let is_uploaded_to_current_client = match window.transfer_queue.get_mut(job_id)
+                    {
+                        Some(job) if job.status.is_active() => {
+                            info!(job_id, "{} finished", job.kind);
+                            job.set_finished(JobStatus::Done);
+
+                            matches!(job.kind, JobKind::Upload { .. })
+                                && job.client_name == window.current_client
+                        }
+                        _ => false,
+                    };
 
-                    NewProcess::GetUploadedFiles.start(window);
+                    if is_uploaded_to_current_client {
+                        NewProcess::GetUploadedFiles.start(window);
+                    }
+                }
+                ProcessResult::JobFailed(job_id, error) => {
+                    warn!(job_id, "{}", error);
+                    let client_name = window
+                        .transfer_queue
+                        .get_mut(job_id)
+                        .map(|job| job.client_name.clone());
+                    if window.transfer_queue.fail(job_id, error) {
+                        if let Some(client_name) = client_name {
+                            info!(session = %client_name, "reconnecting to resume transfers");
+                            NewProcess::ReconnectSession(client_name).start(window);
+                        }
+                    }
                 }
             }
         }
     }
+
+    /// Reloads the files of the current client and reports the files that failed.
+    fn files_changed(window: &mut Window, results: Vec<(File, Result<(), ProcessError>)>) {
+        let total = results.len();
+        let errors = results
+            .into_iter()
+            .filter_map(|(_, result)| result.err())
+            .collect::<Vec<ProcessError>>();
+        for error in &errors {
+            warn!("{}", error);
+        }
+
+        NewProcess::GetUploadedFiles.start(window);
+
+        if !errors.is_empty() {
+            window.current_process = CurrentProcess::PartiallyFailed {
+                succeeded: total - errors.len(),
+                errors,
+            };
+        }
+    }
 }
@@ -0,0 +1,85 @@
+use std::{fmt::Display, time::Duration};
+
+use grammers_mtsender::InvocationError;
+
+/// Why a transient error is worth another attempt.
+#[derive(Debug, Clone)]
+pub enum RetryReason {
+    /// `FLOOD_WAIT` with the time the server asked to wait.
+    RateLimit(Duration),
+    Network,
+    Server,
+}
+
+impl RetryReason {
+    /// Classifies the error, returning `None` for permanent errors.
+    pub fn from_error(error: &InvocationError) -> Option<Self> {
+        match error {
+            InvocationError::Rpc(rpc_error) if rpc_error.code == 420 => Some(
+                RetryReason::RateLimit(Duration::from_secs(rpc_error.value.unwrap_or(1) as u64)),
+            ),
+            InvocationError::Rpc(rpc_error) if rpc_error.code >= 500 => Some(RetryReason::Server),
+            InvocationError::Rpc(_) => None,
+            InvocationError::Dropped | InvocationError::Read(_) => Some(RetryReason::Network),
+        }
+    }
+}
+
+impl Display for RetryReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetryReason::RateLimit(_) => write!(f, "rate limit"),
+            RetryReason::Network => write!(f, "network error"),
+            RetryReason::Server => write!(f, "server error"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grammers_mtproto::mtp::RpcError;
+
+    use super::*;
+
+    fn rpc_error(code: i32, name: &str, value: Option<u32>) -> InvocationError {
+        InvocationError::Rpc(RpcError {
+            code,
+            name: name.to_string(),
+            value,
+            caused_by: None,
+        })
+    }
+
+    #[test]
+    fn flood_wait_is_rate_limit_with_server_delay() {
+        let reason = RetryReason::from_error(&rpc_error(420, "FLOOD_WAIT", Some(30)));
+        assert!(matches!(reason, Some(RetryReason::RateLimit(v)) if v == Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn flood_wait_without_value_waits_a_second() {
+        let reason = RetryReason::from_error(&rpc_error(420, "FLOOD_WAIT", None));
+        assert!(matches!(reason, Some(RetryReason::RateLimit(v)) if v == Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn server_errors_are_retried() {
+        for code in [500, 503] {
+            let reason = RetryReason::from_error(&rpc_error(code, "INTERNAL", None));
+            assert!(matches!(reason, Some(RetryReason::Server)));
+        }
+    }
+
+    #[test]
+    fn request_errors_are_permanent() {
+        for code in [400, 401, 403] {
+            assert!(RetryReason::from_error(&rpc_error(code, "BAD_REQUEST", None)).is_none());
+        }
+    }
+
+    #[test]
+    fn dropped_request_is_network_error() {
+        let reason = RetryReason::from_error(&InvocationError::Dropped);
+        assert!(matches!(reason, Some(RetryReason::Network)));
+    }
+}
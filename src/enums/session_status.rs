@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+use crate::types::{Client, ProcessError};
+
+/// Outcome of loading a saved session on startup.
+#[derive(Debug, Clone)]
+pub enum SessionStatus {
+    Connected,
+    /// The authorization was revoked or expired, the account has to log in again.
+    Revoked,
+    /// The account works, but its `TuchaCloud` chat was not found.
+    CloudChatMissing(Box<Client>),
+    Error(ProcessError),
+}
+
+impl SessionStatus {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, SessionStatus::Connected)
+    }
+}
+
+impl Display for SessionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionStatus::Connected => write!(f, "Connected"),
+            SessionStatus::Revoked => write!(f, "Revoked"),
+            SessionStatus::CloudChatMissing(_) => write!(f, "Cloud chat missing"),
+            SessionStatus::Error(_) => write!(f, "Error"),
+        }
+    }
+}
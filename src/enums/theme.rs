@@ -0,0 +1,35 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Theme {
+    /// Follows the light or dark mode of the operating system.
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Dark, Theme::Light];
+
+    /// Whether dark visuals should be used, `system_is_dark` is `None` when the OS does not tell.
+    pub fn is_dark(&self, system_is_dark: Option<bool>) -> bool {
+        match self {
+            Theme::System => system_is_dark.unwrap_or(true),
+            Theme::Dark => true,
+            Theme::Light => false,
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::System => write!(f, "System"),
+            Theme::Dark => write!(f, "Dark"),
+            Theme::Light => write!(f, "Light"),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use std::time::Duration;
+
+use super::RetryReason;
+
+pub enum TransferEvent {
+    Progress(f32),
+    Waiting(Duration, RetryReason),
+}
@@ -0,0 +1,208 @@
+use std::{env, fs, panic};
+
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::EnvFilter;
+
+use crate::types::APIKeys;
+
+const LOGS_DIRECTORY: &str = "./logs";
+const LOG_FILE_PREFIX: &str = "tucha";
+const MAX_LOG_FILES: usize = 7;
+const DIAGNOSTICS_LINES: usize = 500;
+const DEFAULT_LEVEL: &str = "info";
+const LEVEL_VARIABLE: &str = "TUCHA_LOG";
+const SENSITIVE_KEYS: [&str; 6] = ["password", "code", "token", "phone", "api_hash", "api_id"];
+const REDACTED: &str = "[redacted]";
+
+/// Writes logs to daily rotated files in `./logs`, keeping the last week.
+///
+/// The level is taken from the `TUCHA_LOG` variable (e.g. `TUCHA_LOG=debug`)
+/// and defaults to `info`. The returned guard flushes the logs when dropped.
+pub fn init() -> Option<WorkerGuard> {
+    fs::create_dir_all(LOGS_DIRECTORY).ok()?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(LOGS_DIRECTORY)
+        .ok()?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let level = env::var(LEVEL_VARIABLE).unwrap_or_else(|_| DEFAULT_LEVEL.to_string());
+    let filter = EnvFilter::try_new(format!("tucha={}", level))
+        .unwrap_or_else(|_| EnvFilter::new(format!("tucha={}", DEFAULT_LEVEL)));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(false)
+        .init();
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        tracing::error!("{}", info);
+        default_hook(info);
+    }));
+
+    Some(guard)
+}
+
+/// Version, platform and the most recent log lines, ready to be attached to a
+/// bug report. `secrets` are removed from the text along with the API keys.
+pub fn diagnostics(clients_count: usize, secrets: &[&str]) -> String {
+    let api_keys = APIKeys::load().unwrap_or_else(|| APIKeys::new(0, String::new()));
+    let api_id = api_keys.api_id.to_string();
+    let home_directory = dirs::home_dir()
+        .map(|v| v.display().to_string())
+        .unwrap_or_default();
+
+    let mut secrets = secrets.to_vec();
+    secrets.push(&api_keys.api_hash);
+    secrets.push(&api_id);
+
+    let data_directory = env::current_dir()
+        .map(|v| v.display().to_string())
+        .unwrap_or_default();
+
+    let mut report = format!(
+        "tucha {}\nOS: {} {}\nData directory: {}\nClients: {}\n\nRecent logs:\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH,
+        data_directory,
+        clients_count,
+    );
+    for line in recent_log_lines() {
+        report.push_str(&line);
+        report.push('\n');
+    }
+
+    let mut report = redact(&report, &secrets);
+    if !home_directory.is_empty() {
+        report = report.replace(&home_directory, "~");
+    }
+    report
+}
+
+fn recent_log_lines() -> Vec<String> {
+    let mut log_files = match fs::read_dir(LOGS_DIRECTORY) {
+        Ok(v) => v
+            .flatten()
+            .map(|v| v.path())
+            .filter(|v| {
+                v.file_name()
+                    .and_then(|v| v.to_str())
+                    .is_some_and(|v| v.starts_with(LOG_FILE_PREFIX))
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => return vec![],
+    };
+    // Rotated files are suffixed with the date, so the newest one sorts last.
+    log_files.sort();
+
+    let mut lines = Vec::new();
+    for log_file in log_files.iter().rev().take(2).rev() {
+        if let Ok(content) = fs::read_to_string(log_file) {
+            lines.extend(content.lines().map(|v| v.to_string()));
+        }
+    }
+
+    let skipped = lines.len().saturating_sub(DIAGNOSTICS_LINES);
+    lines.split_off(skipped)
+}
+
+/// Hides the given secrets, values of sensitive `key=value` fields and
+/// anything that looks like a phone number.
+fn redact(text: &str, secrets: &[&str]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets {
+        if secret.len() >= 4 {
+            text = text.replace(secret, REDACTED);
+        }
+    }
+
+    text.lines()
+        .map(|line| {
+            line.split(' ')
+                .map(|word| {
+                    if let Some((key, _)) = word.split_once('=') {
+                        if SENSITIVE_KEYS
+                            .iter()
+                            .any(|v| key.to_lowercase().ends_with(v))
+                        {
+                            return format!("{}={}", key, REDACTED);
+                        }
+                    }
+                    if is_phone_number(word) {
+                        return REDACTED.to_string();
+                    }
+                    word.to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn is_phone_number(word: &str) -> bool {
+    let word = word.trim_matches(|v: char| !v.is_ascii_alphanumeric() && v != '+');
+    match word.strip_prefix('+') {
+        Some(digits) => digits.len() >= 7 && digits.chars().all(|v| v.is_ascii_digit()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_hidden() {
+        let text = redact("connecting with 0123456789abcdef", &["0123456789abcdef"]);
+        assert_eq!(text, format!("connecting with {}", REDACTED));
+    }
+
+    #[test]
+    fn short_secrets_are_ignored() {
+        assert_eq!(redact("id 42 of 420", &["42"]), "id 42 of 420");
+    }
+
+    #[test]
+    fn sensitive_fields_are_hidden() {
+        let text = redact("login code=12345 api_hash=abc session=1", &[]);
+        assert_eq!(
+            text,
+            format!("login code={} api_hash={} session=1", REDACTED, REDACTED)
+        );
+    }
+
+    #[test]
+    fn sensitive_keys_match_by_suffix() {
+        let text = redact("bot_token=123:ABC user_phone=+15551234567", &[]);
+        assert_eq!(
+            text,
+            format!("bot_token={} user_phone={}", REDACTED, REDACTED)
+        );
+    }
+
+    #[test]
+    fn phone_numbers_are_hidden() {
+        let text = redact("sending code to +15551234567 now", &[]);
+        assert_eq!(text, format!("sending code to {} now", REDACTED));
+    }
+
+    #[test]
+    fn short_numbers_are_kept() {
+        assert_eq!(redact("retry +3 after 420", &[]), "retry +3 after 420");
+    }
+
+    #[test]
+    fn lines_are_kept() {
+        assert_eq!(redact("first\nsecond", &[]), "first\nsecond");
+    }
+}
@@ -1,11 +1,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+mod cli;
 mod enums;
+mod logging;
 mod types;
 mod ui;
 mod utils;
 
-use std::{env::set_current_dir, fs};
+use std::{
+    env::{self, set_current_dir},
+    fs, process,
+};
 
 use dirs::data_local_dir;
 use ui::window::Window;
@@ -15,6 +20,12 @@ async fn main() -> Result<(), eframe::Error> {
     if let Some(mut tucha_location) = data_local_dir() {
         tucha_location.push("tucha/sessions");
 
+        if fs::create_dir_all(&tucha_location).is_err() {
+            panic!("Failed to create a local folder on the path: {}", &tucha_location.display().to_string());
+        }
+        tucha_location.pop();
+        tucha_location.push("transfers");
+
         if fs::create_dir_all(&tucha_location).is_err() {
             panic!("Failed to create a local folder on the path: {}", &tucha_location.display().to_string());
         }
@@ -23,6 +34,18 @@ async fn main() -> Result<(), eframe::Error> {
             panic!("Failed to set current directory in path: {}", &tucha_location.display().to_string());
         }
 
+        let log_guard = logging::init();
+        tracing::info!("tucha {} started", env!("CARGO_PKG_VERSION"));
+
+        let args = env::args().skip(1).collect::<Vec<String>>();
+        if !args.is_empty() {
+            #[cfg(windows)]
+            attach_console();
+            let code = cli::run(args).await;
+            drop(log_guard);
+            process::exit(code);
+        }
+
         let options = eframe::NativeOptions {
             ..Default::default()
         };
@@ -32,3 +55,20 @@ async fn main() -> Result<(), eframe::Error> {
         panic!("Failed to get local data directory. Exiting");
     }
 }
+
+/// Release builds have no console of their own, so the output of commands
+/// goes to the console of the shell they were started from.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // Fails harmlessly when there is no parent console or one is attached already.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
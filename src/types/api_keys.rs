@@ -1,13 +1,47 @@
+use std::{env, fs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::enums::ErrorKind;
+
+use super::ProcessError;
+
+pub const API_KEYS_FILE: &str = "./api_keys.json";
+const API_ID_VARIABLE: &str = "TUCHA_API_ID";
+const API_HASH_VARIABLE: &str = "TUCHA_API_HASH";
+
+/// Credentials of the Telegram application, see https://my.telegram.org/apps
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct APIKeys {
     pub api_id: i32,
     pub api_hash: String,
 }
 
 impl APIKeys {
-    pub fn new() -> Self {
-        Self {
-            api_id: 12345678, // insert there your api_id from https://my.telegram.org/apps
-            api_hash: "qwerty".to_string() // insert there your api_hash from https://my.telegram.org/apps
-        }
+    pub fn new(api_id: i32, api_hash: String) -> Self {
+        Self { api_id, api_hash }
+    }
+
+    /// Keys from the `TUCHA_API_ID` and `TUCHA_API_HASH` variables, otherwise from `./api_keys.json`.
+    pub fn load() -> Option<Self> {
+        Self::from_env().or_else(|| {
+            fs::read_to_string(API_KEYS_FILE)
+                .ok()
+                .and_then(|v| serde_json::from_str(&v).ok())
+        })
+    }
+
+    fn from_env() -> Option<Self> {
+        let api_id = env::var(API_ID_VARIABLE).ok()?.trim().parse().ok()?;
+        let api_hash = env::var(API_HASH_VARIABLE).ok()?.trim().to_string();
+
+        Some(Self::new(api_id, api_hash))
+    }
+
+    pub fn save(&self) -> Result<(), ProcessError> {
+        let content = serde_json::to_string(self)
+            .map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+        fs::write(API_KEYS_FILE, content)
+            .map_err(|err| ErrorKind::CannotSaveAPIKeys.with_source(err))
     }
 }
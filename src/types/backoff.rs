@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use grammers_mtsender::InvocationError;
+use tracing::warn;
+
+use crate::enums::{RetryReason, TransferEvent};
+
+const MAX_ATTEMPTS: u32 = 8;
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Retries of a single request: exponential backoff for network and server
+/// errors, and the server-provided wait time for `FLOOD_WAIT`.
+pub struct Backoff {
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self { attempt: 0 }
+    }
+
+    /// Exponential delay before the retry following `attempt` failed attempts.
+    pub fn delay(attempt: u32) -> Duration {
+        BASE_DELAY
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(MAX_DELAY, |v| v.min(MAX_DELAY))
+    }
+
+    /// Whether another attempt is allowed after `attempt` failed attempts.
+    pub fn can_retry(attempt: u32) -> bool {
+        attempt < MAX_ATTEMPTS
+    }
+
+    /// Sleeps before the next attempt if the error is transient, otherwise gives the error back.
+    pub async fn wait(
+        &mut self,
+        error: InvocationError,
+        on_event: &impl Fn(TransferEvent),
+    ) -> Result<(), InvocationError> {
+        let reason = match RetryReason::from_error(&error) {
+            Some(v) if Self::can_retry(self.attempt) => v,
+            _ => return Err(error),
+        };
+
+        let delay = match reason {
+            RetryReason::RateLimit(delay) => delay,
+            RetryReason::Network | RetryReason::Server => Self::delay(self.attempt),
+        };
+        self.attempt += 1;
+        warn!(attempt = self.attempt, ?delay, "retrying after {}", error);
+
+        on_event(TransferEvent::Waiting(delay, reason));
+        tokio::time::sleep(delay).await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use grammers_mtproto::mtp::RpcError;
+
+    use super::*;
+
+    #[test]
+    fn delay_doubles_from_base() {
+        assert_eq!(Backoff::delay(0), BASE_DELAY);
+        assert_eq!(Backoff::delay(1), BASE_DELAY * 2);
+        assert_eq!(Backoff::delay(3), BASE_DELAY * 8);
+    }
+
+    #[test]
+    fn delay_is_capped() {
+        assert_eq!(Backoff::delay(6), MAX_DELAY);
+        assert_eq!(Backoff::delay(MAX_ATTEMPTS), MAX_DELAY);
+        assert_eq!(Backoff::delay(u32::MAX), MAX_DELAY);
+    }
+
+    #[test]
+    fn retries_are_limited() {
+        assert!(Backoff::can_retry(0));
+        assert!(Backoff::can_retry(MAX_ATTEMPTS - 1));
+        assert!(!Backoff::can_retry(MAX_ATTEMPTS));
+    }
+
+    #[tokio::test]
+    async fn permanent_error_is_given_back() {
+        let events = Cell::new(0);
+        let error = InvocationError::Rpc(RpcError {
+            code: 400,
+            name: "FILE_PARTS_INVALID".to_string(),
+            value: None,
+            caused_by: None,
+        });
+
+        let result = Backoff::new()
+            .wait(error, &|_| events.set(events.get() + 1))
+            .await;
+        assert!(matches!(result, Err(InvocationError::Rpc(v)) if v.code == 400));
+        assert_eq!(events.get(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn transient_error_is_reported_and_waited() {
+        let events = Cell::new(0);
+        let mut backoff = Backoff::new();
+
+        let result = backoff
+            .wait(InvocationError::Dropped, &|event| {
+                assert!(matches!(
+                    event,
+                    TransferEvent::Waiting(delay, RetryReason::Network) if delay == BASE_DELAY
+                ));
+                events.set(events.get() + 1);
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(events.get(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_attempts() {
+        let mut backoff = Backoff::new();
+        for _ in 0..MAX_ATTEMPTS {
+            assert!(backoff
+                .wait(InvocationError::Dropped, &|_| {})
+                .await
+                .is_ok());
+        }
+        assert!(backoff
+            .wait(InvocationError::Dropped, &|_| {})
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::enums::TransferDirection;
+
+/// Rates in bytes per second, `0` means unlimited.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RateLimit {
+    pub upload: u64,
+    pub download: u64,
+}
+
+impl RateLimit {
+    pub fn get(&self, direction: TransferDirection) -> u64 {
+        match direction {
+            TransferDirection::Upload => self.upload,
+            TransferDirection::Download => self.download,
+        }
+    }
+}
+
+/// Replaces the global limit between `start_hour` and `end_hour` of local time.
+/// The range wraps around midnight when `start_hour` is greater than `end_hour`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BandwidthSchedule {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    pub limit: RateLimit,
+}
+
+impl BandwidthSchedule {
+    fn contains(&self, hour: u32) -> bool {
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BandwidthLimits {
+    pub global: RateLimit,
+    pub schedules: Vec<BandwidthSchedule>,
+}
+
+impl BandwidthLimits {
+    /// The limit in effect right now: the first matching schedule, otherwise the global one.
+    pub fn current(&self) -> &RateLimit {
+        let hour = Local::now().hour();
+
+        self.schedules
+            .iter()
+            .find(|schedule| schedule.contains(hour))
+            .map(|schedule| &schedule.limit)
+            .unwrap_or(&self.global)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(start_hour: u32, end_hour: u32) -> BandwidthSchedule {
+        BandwidthSchedule {
+            start_hour,
+            end_hour,
+            limit: RateLimit::default(),
+        }
+    }
+
+    #[test]
+    fn schedule_within_a_day() {
+        let schedule = schedule(9, 17);
+        assert!(!schedule.contains(8));
+        assert!(schedule.contains(9));
+        assert!(schedule.contains(16));
+        assert!(!schedule.contains(17));
+    }
+
+    #[test]
+    fn schedule_across_midnight() {
+        let schedule = schedule(22, 6);
+        assert!(schedule.contains(22));
+        assert!(schedule.contains(23));
+        assert!(schedule.contains(0));
+        assert!(schedule.contains(5));
+        assert!(!schedule.contains(6));
+        assert!(!schedule.contains(12));
+        assert!(!schedule.contains(21));
+    }
+
+    #[test]
+    fn empty_schedule_never_applies() {
+        let schedule = schedule(10, 10);
+        assert!((0..24).all(|hour| !schedule.contains(hour)));
+    }
+}
@@ -1,85 +1,235 @@
-use std::{collections::BTreeMap, fs, path::PathBuf, sync::Arc, vec::IntoIter};
+use std::{
+    collections::{BTreeMap, BTreeSet}, fs, io::SeekFrom, path::PathBuf, sync::Arc, time::Instant,
+    vec::IntoIter,
+};
 
 use grammers_client::{
-    types::{Chat, LoginToken, Media, Message, User},
-    Client as TGClient, Config, InputMessage,
+    types::{
+        media::Uploaded,
+        Chat, Downloadable, LoginToken, Media, Message, PasswordToken, User,
+    },
+    Client as TGClient, Config, InitParams, InputMessage, SignInError,
 };
-use grammers_session::Session;
+use grammers_mtsender::InvocationError;
+use grammers_session::{PackedChat, PackedType, Session};
 use grammers_tl_types as tl;
-use tl::{enums, functions::messages::CreateChat, types::InputUser};
+use rand::seq::SliceRandom;
+use tl::functions::{
+    account::GetPassword,
+    auth::{ExportLoginToken, ImportLoginToken},
+    channels::{
+        CreateChannel, DeleteChannel, EditAdmin, EditBanned, EditTitle, GetChannels,
+        GetFullChannel, InviteToChannel,
+    },
+    contacts::ResolvePhone,
+    help::GetNearestDc,
+    messages::{DeleteChat, EditChatTitle, ForwardMessages, GetChats},
+    upload::{SaveBigFilePart, SaveFilePart},
+};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tracing::{debug, info, warn};
 
 use crate::{
-    enums::{ProcessError, ProcessResult},
+    enums::{
+        AccountKind, ErrorKind, JobKind, MemberRole, ProcessResult, SessionStatus, TransferEvent,
+    },
     utils::{self},
 };
 
-use super::{APIKeys, File, FileMetadata, Path};
+use super::{
+    APIKeys, Backoff, Drive, DriveMigration, File, FileMetadata, Migration, Path, ProcessError,
+    QrLogin, Reconnect, ScrubReport, SessionInfo, Settings, ShareTarget, Throttle, TransferState,
+    PART_SIZE,
+};
+
+/// Data center new connections are made to, the same one grammers uses.
+const DEFAULT_DC: i32 = 2;
+/// Files above this size have to be uploaded with `SaveBigFilePart`.
+const BIG_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Bots cannot read the chat history, so their files are found by asking for
+/// message ids in batches until this many batches in a row come back empty.
+const BOT_SCAN_BATCH_SIZE: i32 = 100;
+/// Bot API ids of supergroups and channels are the channel id prefixed with -100.
+const BOT_API_CHANNEL_OFFSET: i64 = 1_000_000_000_000;
+/// Dialogs offered as targets when sharing a file.
+const SHARE_TARGETS_LIMIT: usize = 200;
+/// Files forwarded between accounts at once, Telegram forwards up to 100 messages.
+const TRANSFER_BATCH_SIZE: usize = 50;
 
 #[derive(Clone, Debug)]
 pub struct Client {
     pub tg_client: TGClient,
     pub chat: Option<Chat>,
     pub user: Option<User>,
+    pub drives: Vec<Drive>,
+    /// Drives bound to a chat that no longer exists or cannot be accessed.
+    pub missing_drives: Vec<String>,
+    /// Name of the drive `chat` belongs to.
+    pub drive: String,
 }
 
 impl Client {
-    pub async fn new(tg_client: TGClient, is_completed: bool) -> Result<Self, ProcessError> {
-        if is_completed {
-            let user = tg_client
-                .get_me()
-                .await
-                .map_err(|_| ProcessError::CannotGetUserData)?;
+    /// A client that is not signed in yet.
+    pub fn new(tg_client: TGClient) -> Self {
+        Self {
+            tg_client,
+            chat: None,
+            user: None,
+            drives: vec![],
+            missing_drives: vec![],
+            drive: String::new(),
+        }
+    }
 
-            let mut iter_dialogs = tg_client.iter_dialogs();
+    /// Connects through the proxy from the settings, the account's own one if
+    /// `session_id` has it, otherwise the global one.
+    async fn connect(
+        session: Session,
+        api_keys: &APIKeys,
+        session_id: Option<&str>,
+    ) -> Result<TGClient, ProcessError> {
+        let settings = Settings::load();
+        let proxy_url = settings.proxy_url(session_id);
+        Self::connect_with_proxy(session, api_keys, proxy_url, &settings).await
+    }
 
-            while let Some(dialog) = iter_dialogs
-                .next()
-                .await
-                .map_err(|_| ProcessError::CannotGetDialogs)?
-            {
-                if dialog
-                    .chat()
-                    .name()
-                    .contains(&format!("TuchaCloud-{}", user.id()))
-                {
-                    return Ok(Self {
-                        tg_client,
-                        chat: Some(dialog.chat().clone()),
-                        user: Some(user),
-                    });
-                }
+    /// Also applies the server address and the initial data center from the
+    /// settings, which let tucha run against a local server.
+    async fn connect_with_proxy(
+        session: Session,
+        api_keys: &APIKeys,
+        proxy_url: Option<String>,
+        settings: &Settings,
+    ) -> Result<TGClient, ProcessError> {
+        if let Some(proxy_url) = &proxy_url {
+            check_proxy_url(proxy_url)?;
+        }
+        let server_addr = settings.server_addr()?;
+        // Saved sessions remember their data center, only new ones start at the configured one.
+        if session.get_user().is_none() {
+            if let Some(dc_id) = settings.initial_dc() {
+                session.set_user(0, dc_id, false);
             }
-            Err(ProcessError::ChatIsNone)
-        } else {
-            Ok(Self {
-                tg_client,
-                chat: None,
-                user: None,
-            })
         }
+
+        TGClient::connect(Config {
+            session,
+            api_id: api_keys.api_id,
+            api_hash: api_keys.api_hash.clone(),
+            params: InitParams {
+                // Short flood waits are slept through by grammers for every request,
+                // longer ones reach `Backoff`, which reports them to the UI.
+                reconnection_policy: &Reconnect,
+                proxy_url,
+                server_addr,
+                ..Default::default()
+            },
+        })
+        .await
+        .map_err(|err| ErrorKind::ClientIsNotConnected.with_source(err))
+    }
+
+    /// Connects through `proxy_url`, or directly if it is empty, and measures
+    /// how long it takes to get an answer from Telegram.
+    pub async fn test_connection(proxy_url: String) -> Result<ProcessResult, ProcessError> {
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+        let proxy_url = Some(proxy_url.trim().to_string()).filter(|v| !v.is_empty());
+        info!(with_proxy = proxy_url.is_some(), "testing connection");
+
+        let started = Instant::now();
+        let tg_client =
+            Self::connect_with_proxy(Session::new(), &secret_data, proxy_url, &Settings::load())
+                .await?;
+        tg_client
+            .invoke(&GetNearestDc {})
+            .await
+            .map_err(|err| ErrorKind::ClientIsNotConnected.with_source(err))?;
+
+        Ok(ProcessResult::ConnectionTested(started.elapsed()))
     }
 
     fn get_user(&self) -> Result<&User, ProcessError> {
         match &self.user {
             Some(v) => Ok(v),
-            None => Err(ProcessError::UserIsNone),
+            None => Err(ErrorKind::UserIsNone.into()),
         }
     }
 
     fn get_chat(&self) -> Result<&Chat, ProcessError> {
         match &self.chat {
             Some(v) => Ok(v),
-            None => Err(ProcessError::ChatIsNone),
+            None => Err(ErrorKind::ChatIsNone.into()),
         }
     }
 
-    fn get_username(&self) -> Result<String, ProcessError> {
-        match self.get_user() {
-            Ok(user) => match user.username() {
-                Some(v) => Ok(v.to_string()),
-                None => Err(ProcessError::UsernameIsNone),
-            },
-            Err(err) => Err(err),
+    pub fn is_bot(&self) -> bool {
+        self.user.as_ref().is_some_and(|v| v.is_bot())
+    }
+
+    /// Id of the session, which is the name of its file in `./sessions`.
+    pub fn session_id(&self) -> Result<String, ProcessError> {
+        Ok(self.get_user()?.id().to_string())
+    }
+
+    pub fn session_path(session_id: &str) -> String {
+        format!("./sessions/{}.session", session_id)
+    }
+
+    /// Ids of all sessions saved in `./sessions`, whether they can be connected or not.
+    pub fn saved_session_ids() -> Vec<String> {
+        let mut session_ids = match fs::read_dir("./sessions") {
+            Ok(v) => v
+                .flatten()
+                .map(|v| v.path())
+                .filter(|v| v.extension().is_some_and(|v| v == "session"))
+                .filter_map(|v| v.file_stem().map(|v| v.to_string_lossy().to_string()))
+                .collect::<Vec<String>>(),
+            Err(_) => vec![],
+        };
+        session_ids.sort();
+        session_ids
+    }
+
+    pub fn remove_session(session_id: &str) -> Result<(), ProcessError> {
+        info!(session = session_id, "removing session");
+        fs::remove_file(Self::session_path(session_id))
+            .map_err(|err| ErrorKind::CannotRemoveSession.with_source(err))?;
+        SessionInfo::remove(session_id);
+        Ok(())
+    }
+
+    /// Revokes the authorization on Telegram and removes the local session.
+    pub async fn log_out(self) -> Result<ProcessResult, ProcessError> {
+        let session_id = self.session_id()?;
+        info!(session = %session_id, "logging out");
+
+        self.tg_client
+            .sign_out()
+            .await
+            .map_err(|err| ErrorKind::CannotLogOut.with_source(err))?;
+        Self::remove_session(&session_id)?;
+
+        Ok(ProcessResult::LoggedOut(session_id))
+    }
+
+    /// Name to show for the account: the @username, otherwise the full name or the phone number.
+    pub fn display_name(&self) -> String {
+        let user = match &self.user {
+            Some(v) => v,
+            None => return String::new(),
+        };
+
+        if let Some(username) = user.username() {
+            return format!("@{}", username);
+        }
+        let full_name = user.full_name();
+        if !full_name.trim().is_empty() {
+            return full_name;
+        }
+        match user.phone() {
+            Some(phone) => format!("+{}", phone),
+            None => user.id().to_string(),
         }
     }
 
@@ -87,226 +237,2128 @@ impl Client {
         &self,
         message_ids: &[i32],
     ) -> Result<IntoIter<Option<Message>>, ProcessError> {
-        Ok(self
-            .tg_client
-            .get_messages_by_id(self.get_chat()?, &message_ids)
-            .await
-            .map_err(|_| ProcessError::MessagesNotFound)?
-            .into_iter())
+        let chat = self.get_chat()?;
+
+        let mut backoff = Backoff::new();
+        loop {
+            match self.tg_client.get_messages_by_id(chat, message_ids).await {
+                Ok(v) => return Ok(v.into_iter()),
+                Err(err) => backoff
+                    .wait(err, &|_| {})
+                    .await
+                    .map_err(|err| ErrorKind::MessagesNotFound.with_source(err))?,
+            }
+        }
     }
 
     pub async fn connect_to_saved_sessions() -> Result<ProcessResult, ProcessError> {
-        let mut session_files =
-            fs::read_dir("./sessions").map_err(|_| ProcessError::CannotReadSessionsDirectory)?;
-        let mut clients: BTreeMap<String, Self> = BTreeMap::new();
+        fs::read_dir("./sessions")
+            .map_err(|err| ErrorKind::CannotReadSessionsDirectory.with_source(err))?;
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+
+        let mut sessions = Vec::new();
+        for session_id in Self::saved_session_ids() {
+            let result = Self::connect_saved_session(&session_id, &secret_data).await;
+            sessions.push((session_id, result));
+        }
+        Ok(ProcessResult::ConnectedToSavedClients(sessions))
+    }
 
-        while let Some(Ok(session_file)) = session_files.next() {
-            let path = session_file.path();
-            let secret_data = APIKeys::new();
+    pub async fn reconnect_saved_session(session_id: String) -> Result<ProcessResult, ProcessError> {
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+        let result = Self::connect_saved_session(&session_id, &secret_data).await;
 
-            let tg_client = TGClient::connect(Config {
-                session: Session::load_file(path)
-                    .map_err(|_| ProcessError::CannotLoadSessionFile)?,
-                api_id: secret_data.api_id,
-                api_hash: secret_data.api_hash,
-                params: Default::default(),
-            })
+        Ok(ProcessResult::SessionConnected(session_id, result))
+    }
+
+    /// Connects one saved session, a broken session never stops the others from loading.
+    async fn connect_saved_session(
+        session_id: &str,
+        secret_data: &APIKeys,
+    ) -> Result<Self, SessionStatus> {
+        debug!(session = session_id, "loading saved session");
+
+        let session = Session::load_file(Self::session_path(session_id)).map_err(|err| {
+            SessionStatus::Error(ErrorKind::CannotLoadSessionFile.with_source(err))
+        })?;
+        let tg_client = Self::connect(session, secret_data, Some(session_id))
             .await
-            .map_err(|_| ProcessError::ClientIsNotConnected)?;
+            .map_err(SessionStatus::Error)?;
+
+        let user = match tg_client.get_me().await {
+            Ok(v) => v,
+            Err(InvocationError::Rpc(err)) if is_authorization_lost(&err.name) => {
+                warn!(session = session_id, "session is revoked: {}", err.name);
+                return Err(SessionStatus::Revoked);
+            }
+            Err(err) => {
+                return Err(SessionStatus::Error(
+                    ErrorKind::CannotGetUserData.with_source(err),
+                ))
+            }
+        };
+        let session_info = SessionInfo::load(session_id);
+        let mut client = Self {
+            tg_client,
+            chat: None,
+            user: Some(user),
+            drives: vec![],
+            missing_drives: vec![],
+            drive: String::new(),
+        };
 
-            let client = Self::new(tg_client, true).await?;
+        if session_info.drives.is_empty() && session_info.kind == AccountKind::User {
+            // Sessions saved before chats were bound by id are bound once by the chat title.
+            let user = client.get_user().map_err(SessionStatus::Error)?.clone();
+            client.drives = Self::find_drives(&client.tg_client, &user)
+                .await
+                .map_err(SessionStatus::Error)?;
+            if !client.drives.is_empty() {
+                info!(session = session_id, count = client.drives.len(), "bound drives by title");
+                if let Err(err) = client.save_drives() {
+                    warn!(session = session_id, "cannot save bound drives: {}", err);
+                }
+            }
+        } else {
+            client.bind_drives(&session_info.drives).await;
+        }
 
-            clients.insert(client.get_username()?.to_string(), client);
+        if client.find_drive("").is_err() && !client.missing_drives.iter().any(|v| v.is_empty()) {
+            client.missing_drives.insert(0, String::new());
         }
-        Ok(ProcessResult::ConnectedToSavedClients(clients))
+        // Without the main drive the account still works with its other drives.
+        if !client.select_default_drive() {
+            warn!(session = session_id, "cloud chat is missing");
+            return Err(SessionStatus::CloudChatMissing(Box::new(client)));
+        }
+        info!(session = session_id, drive = %client.drive, "connected saved session");
+
+        Ok(client)
     }
 
-    pub async fn send_login_code(phone_number: String) -> Result<ProcessResult, ProcessError> {
-        let secret_data = APIKeys::new();
+    /// Resolves the chats the drives are bound to. Drives whose chat was upgraded
+    /// to a supergroup are bound to the supergroup from now on.
+    async fn bind_drives(&mut self, bound_drives: &BTreeMap<String, String>) {
+        let mut is_migrated = false;
 
-        let tg_client = TGClient::connect(Config {
-            session: Session::new(),
-            api_id: secret_data.api_id,
-            api_hash: secret_data.api_hash,
-            params: Default::default(),
-        })
-        .await
-        .map_err(|_| ProcessError::ClientIsNotConnected)?;
+        for (name, packed_chat) in bound_drives {
+            match Self::resolve_bound_chat(&self.tg_client, packed_chat).await {
+                Ok((chat, migrated)) => {
+                    is_migrated |= migrated;
+                    self.drives.push(Drive::new(name.clone(), chat));
+                }
+                Err(err) => {
+                    warn!(drive = %name, "chat of the drive is missing: {}", err);
+                    self.missing_drives.push(name.clone());
+                }
+            }
+        }
 
-        let login_token = tg_client
-            .request_login_code(&phone_number)
-            .await
-            .map_err(|_| ProcessError::LoginCodeIsNotSended)?;
-        Ok(ProcessResult::LoginCodeSended(
-            login_token,
-            Client::new(tg_client, false).await?,
-        ))
+        if is_migrated {
+            if let Err(err) = self.save_drives() {
+                warn!("cannot save migrated drives: {}", err);
+            }
+        }
     }
 
-    pub async fn sign_in(
-        self: Self,
-        received_code: String,
-        login_token: Arc<LoginToken>,
-        user_password: String,
-    ) -> Result<ProcessResult, ProcessError> {
-        let user = match self.tg_client.sign_in(&login_token, &received_code).await {
-            Ok(v) => Ok(v),
-            Err(err) => match err {
-                grammers_client::SignInError::SignUpRequired {
-                    terms_of_service: _,
-                } => Err(ProcessError::SignUpRequired),
-                grammers_client::SignInError::PasswordRequired(password_token) => {
-                    if !user_password.is_empty() {
-                        match self
-                            .tg_client
-                            .check_password(password_token, user_password)
+    /// The chat of a packed chat id, and whether it is a supergroup the bound
+    /// basic group was upgraded to.
+    async fn resolve_bound_chat(
+        tg_client: &TGClient,
+        packed_chat: &str,
+    ) -> Result<(Chat, bool), ProcessError> {
+        let packed_chat =
+            PackedChat::from_hex(packed_chat).map_err(|_| ErrorKind::CloudChatIsNotFound)?;
+
+        if let Some(chat_id) = packed_chat.try_to_chat_id() {
+            let chats = match tg_client
+                .invoke(&GetChats { id: vec![chat_id] })
+                .await
+                .map_err(|err| ErrorKind::CloudChatIsNotFound.with_source(err))?
+            {
+                tl::enums::messages::Chats::Chats(v) => v.chats,
+                tl::enums::messages::Chats::Slice(v) => v.chats,
+            };
+
+            match chats.into_iter().next() {
+                Some(tl::enums::Chat::Chat(chat)) => {
+                    if let Some(tl::enums::InputChannel::Channel(channel)) = chat.migrated_to {
+                        info!(
+                            chat_id,
+                            channel_id = channel.channel_id,
+                            "cloud chat was upgraded to a supergroup"
+                        );
+                        let chat = tg_client
+                            .unpack_chat(PackedChat {
+                                ty: PackedType::Megagroup,
+                                id: channel.channel_id,
+                                access_hash: Some(channel.access_hash),
+                            })
                             .await
-                        {
-                            Ok(v) => Ok(v),
-                            Err(_) => Err(ProcessError::OtherSignInError),
-                        }
-                    } else {
-                        Err(ProcessError::PasswordRequired)
+                            .map_err(|err| ErrorKind::CloudChatIsNotFound.with_source(err))?;
+                        return Ok((chat, true));
                     }
+                    if chat.deactivated || chat.left {
+                        return Err(ErrorKind::CloudChatIsNotFound.into());
+                    }
+                }
+                _ => return Err(ErrorKind::CloudChatIsNotFound.into()),
+            }
+        }
+
+        let chat = tg_client
+            .unpack_chat(packed_chat)
+            .await
+            .map_err(|err| ErrorKind::CloudChatIsNotFound.with_source(err))?;
+        Ok((chat, false))
+    }
+
+    /// Remembers the chat ids of the drives next to the session. Missing drives
+    /// keep their old binding until they are recreated, rebound or deleted.
+    fn save_drives(&self) -> Result<(), ProcessError> {
+        let session_id = self.session_id()?;
+        let mut session_info = SessionInfo::load(&session_id);
+
+        session_info
+            .drives
+            .retain(|name, _| self.missing_drives.contains(name));
+        for drive in &self.drives {
+            session_info
+                .drives
+                .insert(drive.name.clone(), drive.chat.pack().to_hex());
+        }
+        session_info.save(&session_id)
+    }
+
+    /// Adds the drive or replaces the one with the same name.
+    fn set_drive(&mut self, drive: Drive) {
+        self.drives.retain(|v| v.name != drive.name);
+        self.missing_drives.retain(|v| *v != drive.name);
+        self.drives.push(drive);
+        self.drives.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Every drive of the account found among its dialogs, the main one first.
+    async fn find_drives(tg_client: &TGClient, user: &User) -> Result<Vec<Drive>, ProcessError> {
+        let mut drives = Vec::<Drive>::new();
+        let mut iter_dialogs = tg_client.iter_dialogs();
+
+        while let Some(dialog) = iter_dialogs
+            .next()
+            .await
+            .map_err(|err| ErrorKind::CannotGetDialogs.with_source(err))?
+        {
+            if let Some(drive) = Drive::from_chat(user.id(), dialog.chat()) {
+                // Dialogs come most recent first, so the most recently used chat wins.
+                if !drives.iter().any(|v| v.name == drive.name) {
+                    drives.push(drive);
+                }
+            }
+        }
+        drives.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(drives)
+    }
+
+    /// Makes `name` the drive files are read from and stored in.
+    pub fn select_drive(&mut self, name: &str) -> bool {
+        match self.drives.iter().find(|v| v.name == name) {
+            Some(drive) => {
+                self.chat = Some(drive.chat.clone());
+                self.drive = drive.name.clone();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Selects the main drive, or the first other one if the main drive is missing.
+    /// Returns `false` if the account has no drive at all.
+    pub fn select_default_drive(&mut self) -> bool {
+        let name = match self.find_drive("") {
+            Ok(_) => String::new(),
+            Err(_) => match self.drives.first() {
+                Some(drive) => drive.name.clone(),
+                None => {
+                    self.chat = None;
+                    return false;
                 }
-                grammers_client::SignInError::InvalidCode => Err(ProcessError::InvalidCode),
-                grammers_client::SignInError::InvalidPassword => Err(ProcessError::InvalidPassword),
-                grammers_client::SignInError::Other(_) => Err(ProcessError::OtherSignInError),
             },
-        }?;
+        };
+        self.select_drive(&name)
+    }
 
-        let path = format!("sessions/{}.session", user.id());
-        fs::write(&path, "").map_err(|_| ProcessError::SessionFileIsNotExist)?;
-        self.tg_client
-            .session()
-            .save_to_file(path)
-            .map_err(|_| ProcessError::CannotSaveSessionInFile)?;
+    fn find_drive(&self, name: &str) -> Result<&Drive, ProcessError> {
+        self.drives
+            .iter()
+            .find(|v| v.name == name)
+            .ok_or(ErrorKind::DriveIsNotFound.into())
+    }
 
-        if self.get_chat().is_err() {
-            self.tg_client
-                .invoke(&CreateChat {
-                    users: vec![enums::InputUser::User(InputUser {
-                        user_id: user.id(),
-                        access_hash: match user.pack().access_hash {
-                            Some(v) => v,
-                            None => return Err(ProcessError::AccessHashIsNone),
-                        },
-                    })],
-                    title: format!("TuchaCloud-{}", user.id()),
-                    ttl_period: None,
+    fn check_drive_name(&self, name: &str) -> Result<String, ProcessError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ErrorKind::InvalidDriveName.into());
+        }
+        let is_taken = self.drives.iter().any(|v| v.name == name)
+            || self.missing_drives.iter().any(|v| *v == name);
+        if is_taken {
+            return Err(ErrorKind::DriveAlreadyExists.into());
+        }
+        Ok(name.to_string())
+    }
+
+    pub async fn create_drive(mut self, name: String) -> Result<ProcessResult, ProcessError> {
+        let name = self.check_drive_name(&name)?;
+        let user = self.get_user()?.clone();
+        if user.is_bot() {
+            return Err(ErrorKind::BotCannotCreateChat.into());
+        }
+
+        let chat = self.create_drive_chat(&user, &name).await?;
+        self.set_drive(Drive::new(name.clone(), chat));
+        self.save_drives()?;
+        self.select_drive(&name);
+
+        Ok(ProcessResult::DrivesUpdated(self.session_id()?, self))
+    }
+
+    /// Adds a drive stored in a chat another account shared, which the title
+    /// scan never finds because the title carries the owner's id.
+    pub async fn add_shared_drive(
+        mut self,
+        name: String,
+        address: String,
+    ) -> Result<ProcessResult, ProcessError> {
+        let name = self.check_drive_name(&name)?;
+        let chat = Self::resolve_storage_chat(&self.tg_client, &address, self.is_bot()).await?;
+        info!(drive = %name, chat_id = chat.id(), "adding shared drive");
+
+        self.set_drive(Drive::new(name.clone(), chat));
+        self.save_drives()?;
+        self.select_drive(&name);
+
+        Ok(ProcessResult::DrivesUpdated(self.session_id()?, self))
+    }
+
+    /// Creates a new chat for a drive whose chat is missing.
+    pub async fn recreate_drive(mut self, name: String) -> Result<ProcessResult, ProcessError> {
+        let user = self.get_user()?.clone();
+        if user.is_bot() {
+            return Err(ErrorKind::BotCannotCreateChat.into());
+        }
+
+        let chat = self.create_drive_chat(&user, &name).await?;
+        self.set_drive(Drive::new(name.clone(), chat));
+        self.save_drives()?;
+        if self.chat.is_none() || self.drive == name {
+            self.select_drive(&name);
+        }
+
+        Ok(ProcessResult::DrivesUpdated(self.session_id()?, self))
+    }
+
+    /// Binds the drive to another existing chat, given by @username or id.
+    pub async fn rebind_drive(
+        mut self,
+        name: String,
+        address: String,
+    ) -> Result<ProcessResult, ProcessError> {
+        let chat = Self::resolve_storage_chat(&self.tg_client, &address, self.is_bot()).await?;
+        info!(drive = %name, chat_id = chat.id(), "rebinding drive");
+
+        self.set_drive(Drive::new(name.clone(), chat));
+        self.save_drives()?;
+        if self.chat.is_none() || self.drive == name {
+            self.select_drive(&name);
+        }
+
+        Ok(ProcessResult::DrivesUpdated(self.session_id()?, self))
+    }
+
+    pub async fn rename_drive(
+        mut self,
+        name: String,
+        new_name: String,
+    ) -> Result<ProcessResult, ProcessError> {
+        let new_name = self.check_drive_name(&new_name)?;
+        if name.is_empty() {
+            return Err(ErrorKind::MainDriveCannotBeChanged.into());
+        }
+        let user_id = self.get_user()?.id();
+        let drive = self.find_drive(&name)?;
+        let packed_chat = drive.chat.pack();
+        // Drives shared by others and chats bound by hand keep their titles, only
+        // the name they are bound under changes.
+        let is_titled = Drive::from_chat(user_id, &drive.chat).is_some_and(|v| v.name == name);
+        let title = Drive::title(user_id, &new_name);
+        info!(drive = %name, new_name = %new_name, is_titled, "renaming drive");
+
+        let result = match packed_chat.try_to_input_channel() {
+            _ if !is_titled => Ok(()),
+            Some(channel) => self
+                .tg_client
+                .invoke(&EditTitle { channel, title })
+                .await
+                .map(drop),
+            None => self
+                .tg_client
+                .invoke(&EditChatTitle {
+                    chat_id: packed_chat.id,
+                    title,
                 })
                 .await
-                .map_err(|_| ProcessError::CloudGroupIsNotCreated)?;
+                .map(drop),
+        };
+        result.map_err(|err| ErrorKind::CannotChangeDrive.with_source(err))?;
+
+        let chat = self.find_drive(&name)?.chat.clone();
+        self.drives.retain(|v| v.name != name);
+        self.set_drive(Drive::new(new_name.clone(), chat));
+        self.save_drives()?;
+        if self.drive == name {
+            self.select_drive(&new_name);
+        }
+
+        Ok(ProcessResult::DrivesUpdated(self.session_id()?, self))
+    }
+
+    /// Deletes the chat of the drive together with every file in it.
+    pub async fn delete_drive(mut self, name: String) -> Result<ProcessResult, ProcessError> {
+        if name.is_empty() {
+            return Err(ErrorKind::MainDriveCannotBeChanged.into());
+        }
+        // There is no chat left to delete, the drive only has to be forgotten.
+        if self.missing_drives.contains(&name) {
+            self.missing_drives.retain(|v| *v != name);
+            self.save_drives()?;
+            return Ok(ProcessResult::DrivesUpdated(self.session_id()?, self));
         }
+        let packed_chat = self.find_drive(&name)?.chat.pack();
+        info!(drive = %name, "deleting drive");
+
+        let result = match packed_chat.try_to_input_channel() {
+            Some(channel) => self
+                .tg_client
+                .invoke(&DeleteChannel { channel })
+                .await
+                .map(drop),
+            None => self
+                .tg_client
+                .invoke(&DeleteChat {
+                    chat_id: packed_chat.id,
+                })
+                .await
+                .map(drop),
+        };
+        result.map_err(|err| ErrorKind::CannotChangeDrive.with_source(err))?;
 
-        let client = Client::new(self.tg_client, true).await?;
+        self.drives.retain(|v| v.name != name);
+        self.save_drives()?;
+        if self.drive == name {
+            self.select_default_drive();
+        }
 
-        Ok(ProcessResult::LoggedIn(
-            client.clone(),
-            client.get_username()?,
-        ))
+        Ok(ProcessResult::DrivesUpdated(self.session_id()?, self))
     }
 
-    pub async fn upload_files(
-        self: Self,
-        transferred_files: Vec<PathBuf>,
-        path: Path,
+    /// Adds a Telegram user to the chat of the drive, or changes the role of
+    /// a member already there. Viewers are restricted from posting, editors and
+    /// owners become admins with the rights of their role.
+    pub async fn invite_member(
+        self,
+        name: String,
+        username: String,
+        role: MemberRole,
     ) -> Result<ProcessResult, ProcessError> {
-        for file in transferred_files {
-            let file_metadata = FileMetadata::new(
-                path.join(
-                    file.file_name()
-                        .ok_or(ProcessError::CannotGetFileName)?
-                        .to_str()
-                        .ok_or(ProcessError::CannotGetFileName)?,
-                )
-                .path(),
-            );
+        let channel = self
+            .find_drive(&name)?
+            .chat
+            .pack()
+            .try_to_input_channel()
+            .ok_or(ErrorKind::DriveIsNotShareable)?;
 
-            let message = InputMessage::text(
-                serde_json::to_string(&file_metadata)
-                    .map_err(|_| ProcessError::CannotSerializeToString)?,
-            )
-            .document(
-                self.tg_client
-                    .upload_file(file.as_os_str())
-                    .await
-                    .map_err(|_| ProcessError::CannotUploadFile)?,
-            );
+        let username = username.trim().trim_start_matches('@');
+        let member = match self.tg_client.resolve_username(username).await {
+            Ok(Some(Chat::User(user))) => user.pack(),
+            Ok(_) => return Err(ErrorKind::MemberIsNotFound.into()),
+            Err(err) => return Err(ErrorKind::MemberIsNotFound.with_source(err)),
+        };
+        let input_user = member
+            .try_to_input_user()
+            .ok_or(ErrorKind::MemberIsNotFound)?;
+        info!(drive = %name, member_id = member.id, %role, "inviting member");
 
-            let _message = &self
-                .tg_client
-                .send_message(self.get_chat()?, message)
+        match self
+            .tg_client
+            .invoke(&InviteToChannel {
+                channel: channel.clone(),
+                users: vec![input_user.clone()],
+            })
+            .await
+        {
+            Ok(_) => {}
+            Err(InvocationError::Rpc(err)) if err.name == "USER_ALREADY_PARTICIPANT" => {}
+            Err(err) => return Err(ErrorKind::CannotInviteMember.with_source(err)),
+        }
+
+        let admin_rights = tl::types::ChatAdminRights {
+            change_info: role == MemberRole::Owner,
+            post_messages: role != MemberRole::Viewer,
+            edit_messages: role != MemberRole::Viewer,
+            delete_messages: role == MemberRole::Owner,
+            ban_users: role == MemberRole::Owner,
+            invite_users: role == MemberRole::Owner,
+            pin_messages: role != MemberRole::Viewer,
+            add_admins: false,
+            anonymous: false,
+            manage_call: false,
+            other: role != MemberRole::Viewer,
+            manage_topics: false,
+            post_stories: false,
+            edit_stories: false,
+            delete_stories: false,
+        };
+        let result = self
+            .tg_client
+            .invoke(&EditAdmin {
+                channel: channel.clone(),
+                user_id: input_user,
+                admin_rights: admin_rights.into(),
+                rank: role.to_string(),
+            })
+            .await;
+        match result {
+            Ok(_) => {}
+            // Taking admin rights from someone who never had them is not an error for a viewer.
+            Err(err) if role == MemberRole::Viewer => {
+                debug!(member_id = member.id, "member was not an admin: {}", err);
+            }
+            Err(err) => return Err(ErrorKind::CannotSetMemberRole.with_source(err)),
+        }
+
+        if role == MemberRole::Viewer {
+            self.tg_client
+                .invoke(&EditBanned {
+                    channel,
+                    participant: member.to_input_peer(),
+                    banned_rights: Self::viewer_rights().into(),
+                })
                 .await
-                .map_err(|_| ProcessError::MediaMessageIsNotSended)?;
+                .map_err(|err| ErrorKind::CannotSetMemberRole.with_source(err))?;
         }
 
-        Ok(ProcessResult::FilesUploaded)
+        Ok(ProcessResult::MemberInvited(username.to_string(), role))
     }
 
-    pub async fn get_uploaded_files(self) -> Result<ProcessResult, ProcessError> {
-        let mut files = Vec::<File>::new();
-        let mut messages = self.tg_client.iter_messages(&self.get_chat()?.clone());
+    /// Members with these restrictions can read the chat but not post to it.
+    fn viewer_rights() -> tl::types::ChatBannedRights {
+        tl::types::ChatBannedRights {
+            view_messages: false,
+            send_messages: true,
+            send_media: true,
+            send_stickers: true,
+            send_gifs: true,
+            send_games: true,
+            send_inline: true,
+            embed_links: true,
+            send_polls: true,
+            change_info: true,
+            invite_users: true,
+            pin_messages: true,
+            manage_topics: true,
+            send_photos: true,
+            send_videos: true,
+            send_roundvideos: true,
+            send_audios: true,
+            send_voices: true,
+            send_docs: true,
+            send_plain: true,
+            until_date: 0,
+        }
+    }
 
-        while let Some(message) = messages
+    /// Chats and contacts from the most recent dialogs, without cloud chats.
+    pub async fn get_share_targets(self) -> Result<ProcessResult, ProcessError> {
+        let user = self.get_user()?;
+        let mut targets = Vec::<ShareTarget>::new();
+        let mut iter_dialogs = self.tg_client.iter_dialogs().limit(SHARE_TARGETS_LIMIT);
+
+        while let Some(dialog) = iter_dialogs
             .next()
             .await
-            .map_err(|_| ProcessError::CannotReadMessages)?
+            .map_err(|err| ErrorKind::CannotGetDialogs.with_source(err))?
         {
-            if let Ok(file_metadata) = serde_json::from_str::<FileMetadata>(message.text()) {
-                if let Some(media) = message.media() {
-                    match media {
-                        Media::Document(_) | Media::Sticker(_) => {
-                            let file = File::new(file_metadata, message.id());
-
-                            files.push(file);
-                        }
-                        _ => {}
-                    }
-                }
+            let chat = dialog.chat();
+            if Drive::from_chat(user.id(), chat).is_none() {
+                targets.push(ShareTarget::new(chat.name().to_string(), chat.pack()));
             }
         }
 
-        Ok(ProcessResult::UploadedFilesReceived(
-            self.get_username()?,
-            files,
-        ))
+        Ok(ProcessResult::ShareTargetsReceived(self.session_id()?, targets))
     }
 
-    pub async fn download_files(
+    /// Forwards the message of the file to the target without the author and the
+    /// metadata caption. The media is sent by reference, so nothing is uploaded again.
+    pub async fn share_file(
         self,
-        message_ids: Vec<i32>,
+        file: File,
+        target: ShareTarget,
     ) -> Result<ProcessResult, ProcessError> {
-        let mut messages = self.get_messages_by_id(&message_ids).await?;
+        let chat = self.get_chat()?;
+        info!(message_id = file.message_id, target = %target.name, "sharing file");
 
-        while let Some(Some(message)) = messages.next() {
-            if let Some(media) = message.clone().media() {
-                match media {
-                    Media::Document(document) => {
-                        utils::download_file(message, document.name()).await?
-                    }
-                    Media::Sticker(sticker) => {
-                        utils::download_file(message, sticker.document.name()).await?
-                    }
-                    _ => {}
-                }
-            } else {
-                return Err(ProcessError::MessageNotContainsMedia);
-            }
+        self.tg_client
+            .invoke(&ForwardMessages {
+                silent: false,
+                background: false,
+                with_my_score: false,
+                // Telegram drops captions only from forwards without the author,
+                // otherwise the metadata JSON would reach the target.
+                drop_author: true,
+                drop_media_captions: true,
+                noforwards: false,
+                from_peer: chat.pack().to_input_peer(),
+                id: vec![file.message_id],
+                random_id: vec![rand::random()],
+                to_peer: target.chat.to_input_peer(),
+                top_msg_id: None,
+                schedule_date: None,
+                send_as: None,
+                quick_reply_shortcut: None,
+            })
+            .await
+            .map_err(|err| ErrorKind::CannotShareFile.with_source(err))?;
+
+        Ok(ProcessResult::FileShared(target.name))
+    }
+
+    /// `t.me` link to the message of the file. Only messages of supergroups
+    /// and channels have links, private ones open for their members only.
+    pub fn file_link(&self, file: &File) -> Option<String> {
+        let chat = self.chat.as_ref()?;
+        match chat.pack().ty {
+            PackedType::Megagroup | PackedType::Broadcast | PackedType::Gigagroup => {}
+            _ => return None,
         }
 
-        Ok(ProcessResult::FilesDownloaded)
+        Some(match chat.username() {
+            Some(username) => format!("https://t.me/{}/{}", username, file.message_id),
+            None => format!("https://t.me/c/{}/{}", chat.id(), file.message_id),
+        })
     }
-    pub async fn delete_files(self, message_ids: Vec<i32>) -> Result<ProcessResult, ProcessError> {
-        let mut messages = self.get_messages_by_id(&message_ids).await?;
 
-        while let Some(Some(message)) = messages.next() {
-            message
-                .delete()
+    /// Finds a chat to store files in by its @username or its id, either as
+    /// shown by the Bot API (`-100...`) or without the prefix.
+    async fn resolve_storage_chat(
+        tg_client: &TGClient,
+        address: &str,
+        is_bot: bool,
+    ) -> Result<Chat, ProcessError> {
+        let address = address.trim();
+        let chat = match address.parse::<i64>() {
+            // Users know the access hashes of their chats only from their dialogs.
+            Ok(id) if !is_bot => Self::find_dialog(tg_client, id.abs() % BOT_API_CHANNEL_OFFSET)
+                .await?
+                .ok_or(ErrorKind::CloudChatIsNotFound)?,
+            Ok(id) => {
+                let id = if id < 0 { -id - BOT_API_CHANNEL_OFFSET } else { id };
+                tg_client
+                    .unpack_chat(Self::resolve_bot_channel(tg_client, id).await?)
+                    .await
+                    .map_err(|err| ErrorKind::StorageChatIsNotFound.with_source(err))?
+            }
+            Err(_) => tg_client
+                .resolve_username(address.trim_start_matches('@'))
                 .await
-                .map_err(|_| ProcessError::CannotDeleteFile)?;
+                .map_err(|err| ErrorKind::StorageChatIsNotFound.with_source(err))?
+                .ok_or(ErrorKind::StorageChatIsNotFound)?,
+        };
+
+        match chat.pack().ty {
+            PackedType::Megagroup | PackedType::Broadcast | PackedType::Gigagroup => Ok(chat),
+            PackedType::Chat if !is_bot => Ok(chat),
+            _ if is_bot => Err(ErrorKind::StorageChatIsNotChannel.into()),
+            _ => Err(ErrorKind::ChatIsNotGroup.into()),
+        }
+    }
+
+    /// Bots may refer to channels they are in without knowing the access hash.
+    /// The channel itself tells whether it is a broadcast channel or a group,
+    /// and its real access hash, which is saved when the drive is bound.
+    async fn resolve_bot_channel(
+        tg_client: &TGClient,
+        channel_id: i64,
+    ) -> Result<PackedChat, ProcessError> {
+        let chats = match tg_client
+            .invoke(&GetChannels {
+                id: vec![tl::types::InputChannel {
+                    channel_id,
+                    access_hash: 0,
+                }
+                .into()],
+            })
+            .await
+            .map_err(|err| ErrorKind::StorageChatIsNotFound.with_source(err))?
+        {
+            tl::enums::messages::Chats::Chats(v) => v.chats,
+            tl::enums::messages::Chats::Slice(v) => v.chats,
+        };
+
+        match chats.into_iter().next() {
+            Some(tl::enums::Chat::Channel(channel)) => Ok(PackedChat {
+                ty: if channel.gigagroup {
+                    PackedType::Gigagroup
+                } else if channel.broadcast {
+                    PackedType::Broadcast
+                } else {
+                    PackedType::Megagroup
+                },
+                id: channel.id,
+                access_hash: Some(channel.access_hash.unwrap_or(0)),
+            }),
+            Some(_) => Err(ErrorKind::StorageChatIsNotChannel.into()),
+            None => Err(ErrorKind::StorageChatIsNotFound.into()),
+        }
+    }
+
+    async fn find_dialog(tg_client: &TGClient, chat_id: i64) -> Result<Option<Chat>, ProcessError> {
+        let mut iter_dialogs = tg_client.iter_dialogs();
+
+        while let Some(dialog) = iter_dialogs
+            .next()
+            .await
+            .map_err(|err| ErrorKind::CannotGetDialogs.with_source(err))?
+        {
+            if dialog.chat().id() == chat_id {
+                return Ok(Some(dialog.chat().clone()));
+            }
         }
+        Ok(None)
+    }
+
+    /// Creates a private supergroup for the drive. Unlike basic groups,
+    /// supergroups are never migrated, so their id stays the same.
+    async fn create_drive_chat(&self, user: &User, name: &str) -> Result<Chat, ProcessError> {
+        info!(user_id = user.id(), drive = name, "creating cloud supergroup");
+        let updates = self
+            .tg_client
+            .invoke(&CreateChannel {
+                broadcast: false,
+                megagroup: true,
+                for_import: false,
+                forum: false,
+                title: Drive::title(user.id(), name),
+                about: String::from("Files stored by Tucha."),
+                geo_point: None,
+                address: None,
+                ttl_period: None,
+            })
+            .await
+            .map_err(|err| ErrorKind::CloudGroupIsNotCreated.with_source(err))?;
+
+        let chats = match updates {
+            tl::enums::Updates::Updates(v) => v.chats,
+            tl::enums::Updates::Combined(v) => v.chats,
+            _ => vec![],
+        };
+        let packed_chat = chats
+            .iter()
+            .find_map(|chat| match chat {
+                tl::enums::Chat::Channel(v) => Some(PackedChat {
+                    ty: PackedType::Megagroup,
+                    id: v.id,
+                    access_hash: v.access_hash,
+                }),
+                _ => None,
+            })
+            .ok_or(ErrorKind::CloudGroupIsNotCreated)?;
+
+        self.tg_client
+            .unpack_chat(packed_chat)
+            .await
+            .map_err(|err| ErrorKind::CloudGroupIsNotCreated.with_source(err))
+    }
+
+    /// Recreates the cloud chat of a connected account whose chat is missing.
+    pub async fn repair_cloud_chat(self) -> Result<ProcessResult, ProcessError> {
+        self.recreate_drive(String::new()).await
+    }
+
+    pub async fn send_login_code(phone_number: String) -> Result<ProcessResult, ProcessError> {
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+        info!("requesting login code");
+
+        let tg_client = Self::connect(Session::new(), &secret_data, None).await?;
+
+        let login_token = tg_client
+            .request_login_code(&phone_number)
+            .await
+            .map_err(|err| ErrorKind::LoginCodeIsNotSended.with_source(err))?;
+        Ok(ProcessResult::LoginCodeSended(login_token, Client::new(tg_client)))
+    }
+
+    /// Checks the keys against Telegram and saves them if they are accepted.
+    pub async fn test_api_keys(api_keys: APIKeys) -> Result<ProcessResult, ProcessError> {
+        info!("testing API keys");
+        let tg_client = Self::connect(Session::new(), &api_keys, None).await?;
+
+        // Exporting a login token validates the keys without sending anything to the user.
+        tg_client
+            .invoke(&ExportLoginToken {
+                api_id: api_keys.api_id,
+                api_hash: api_keys.api_hash.clone(),
+                except_ids: vec![],
+            })
+            .await
+            .map_err(|err| match &err {
+                InvocationError::Rpc(rpc_error) if rpc_error.name.starts_with("API_ID") => {
+                    ErrorKind::InvalidAPIKeys.with_source(err)
+                }
+                _ => ErrorKind::ClientIsNotConnected.with_source(err),
+            })?;
+
+        api_keys.save()?;
+        Ok(ProcessResult::APIKeysSaved)
+    }
+
+    pub async fn sign_in(
+        self,
+        received_code: String,
+        login_token: Arc<LoginToken>,
+        user_password: String,
+    ) -> Result<ProcessResult, ProcessError> {
+        let user = match self.tg_client.sign_in(&login_token, &received_code).await {
+            Ok(v) => v,
+            Err(SignInError::PasswordRequired(password_token)) if !user_password.is_empty() => self
+                .tg_client
+                .check_password(password_token, user_password)
+                .await
+                .map_err(sign_in_error)?,
+            Err(err) => return Err(sign_in_error(err)),
+        };
+
+        self.finish_login(user).await
+    }
+
+    /// Logs in as a bot that keeps files in `chat_address`, a supergroup or a
+    /// channel where it is an admin.
+    pub async fn bot_sign_in(
+        bot_token: String,
+        chat_address: String,
+    ) -> Result<ProcessResult, ProcessError> {
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+        info!("logging in as a bot");
+
+        let tg_client = Self::connect(Session::new(), &secret_data, None).await?;
+
+        let user = tg_client
+            .bot_sign_in(bot_token.trim())
+            .await
+            .map_err(|err| ErrorKind::InvalidBotToken.with_source(err))?;
+        let chat = Self::resolve_storage_chat(&tg_client, &chat_address, true).await?;
+        info!(user_id = user.id(), chat_id = chat.id(), "signed in as a bot");
+
+        let session_id = user.id().to_string();
+        let client = Self {
+            tg_client,
+            chat: Some(chat.clone()),
+            user: Some(user),
+            drives: vec![Drive::new(String::new(), chat)],
+            missing_drives: vec![],
+            drive: String::new(),
+        };
+        client.save_session(&session_id)?;
+        SessionInfo::new(AccountKind::Bot).save(&session_id)?;
+        client.save_drives()?;
+
+        Ok(ProcessResult::LoggedIn(client, session_id))
+    }
+
+    /// Starts a QR code login on a new connection.
+    pub async fn start_qr_login() -> Result<ProcessResult, ProcessError> {
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+        info!("starting QR code login");
+
+        let tg_client = Self::connect(Session::new(), &secret_data, None).await?;
+
+        Client::new(tg_client)
+            .poll_qr_login(String::new())
+            .await
+    }
+
+    /// Exports the login token again: it either is a fresh token to show, or the
+    /// QR code was scanned and the login can be finished.
+    pub async fn poll_qr_login(self, user_password: String) -> Result<ProcessResult, ProcessError> {
+        let secret_data = APIKeys::load().ok_or(ErrorKind::APIKeysAreNotSet)?;
+
+        let result = self
+            .tg_client
+            .invoke(&ExportLoginToken {
+                api_id: secret_data.api_id,
+                api_hash: secret_data.api_hash.clone(),
+                except_ids: vec![],
+            })
+            .await;
+
+        match result {
+            Ok(tl::enums::auth::LoginToken::Token(token)) => Ok(ProcessResult::QrLoginTokenReceived(
+                self,
+                QrLogin::new(&token.token, token.expires),
+            )),
+            Ok(tl::enums::auth::LoginToken::Success(success)) => {
+                let dc_id = self
+                    .tg_client
+                    .session()
+                    .get_user()
+                    .map_or(DEFAULT_DC, |v| v.dc);
+                self.finish_qr_login(success.authorization, dc_id).await
+            }
+            // The account lives in another data center, the token has to be imported there.
+            Ok(tl::enums::auth::LoginToken::MigrateTo(migrate_to)) => {
+                info!(dc_id = migrate_to.dc_id, "QR code login moves to another data center");
+                let session = Session::new();
+                // A placeholder user makes `connect` use the data center of the account.
+                session.set_user(0, migrate_to.dc_id, false);
+
+                let tg_client = Self::connect(session, &secret_data, None).await?;
+                let client = Client::new(tg_client);
+
+                let result = client
+                    .tg_client
+                    .invoke(&ImportLoginToken {
+                        token: migrate_to.token,
+                    })
+                    .await;
+                match result {
+                    Ok(tl::enums::auth::LoginToken::Success(success)) => {
+                        client
+                            .finish_qr_login(success.authorization, migrate_to.dc_id)
+                            .await
+                    }
+                    Ok(_) => Err(ErrorKind::OtherSignInError.into()),
+                    Err(InvocationError::Rpc(err)) if err.name == "SESSION_PASSWORD_NEEDED" => {
+                        client.check_qr_password(user_password).await
+                    }
+                    Err(err) => Err(ErrorKind::OtherSignInError.with_source(err)),
+                }
+            }
+            Err(InvocationError::Rpc(err)) if err.name == "SESSION_PASSWORD_NEEDED" => {
+                self.check_qr_password(user_password).await
+            }
+            Err(err) => Err(ErrorKind::OtherSignInError.with_source(err)),
+        }
+    }
+
+    /// The scanned account has two-step verification, so the login needs its password.
+    async fn check_qr_password(self, user_password: String) -> Result<ProcessResult, ProcessError> {
+        if user_password.is_empty() {
+            return Ok(ProcessResult::QrPasswordRequired(self));
+        }
+
+        let password = self
+            .tg_client
+            .invoke(&GetPassword {})
+            .await
+            .map_err(|err| ErrorKind::OtherSignInError.with_source(err))?;
+        let tl::enums::account::Password::Password(password) = password;
+
+        let user = self
+            .tg_client
+            .check_password(PasswordToken::new(password), user_password)
+            .await
+            .map_err(sign_in_error)?;
+
+        self.finish_login(user).await
+    }
+
+    async fn finish_qr_login(
+        self,
+        authorization: tl::enums::auth::Authorization,
+        dc_id: i32,
+    ) -> Result<ProcessResult, ProcessError> {
+        let user = match authorization {
+            tl::enums::auth::Authorization::Authorization(v) => User::from_raw(v.user),
+            tl::enums::auth::Authorization::SignUpRequired(_) => {
+                return Err(ErrorKind::SignUpRequired.into())
+            }
+        };
+        // `complete_login` of grammers is private, so the session is told about the user here.
+        self.tg_client.session().set_user(user.id(), dc_id, false);
+
+        self.finish_login(user).await
+    }
+
+    /// Saves the session of a freshly authorized client and looks for the cloud
+    /// the account already has, so logging in again does not create another one.
+    async fn finish_login(mut self, user: User) -> Result<ProcessResult, ProcessError> {
+        let session_id = user.id().to_string();
+        info!(user_id = user.id(), "signed in");
+        self.save_session(&session_id)?;
+        self.user = Some(user.clone());
+
+        // Chats bound by id during a previous login, then cloud chats found by title.
+        self.bind_drives(&SessionInfo::load(&session_id).drives).await;
+        self.missing_drives.clear();
+        for drive in Self::find_drives(&self.tg_client, &user).await? {
+            if !self.drives.iter().any(|v| v.name == drive.name) {
+                self.drives.push(drive);
+            }
+        }
+        self.drives.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if self.drives.is_empty() {
+            return self.provision_cloud(false).await;
+        }
+        info!(session = %session_id, count = self.drives.len(), "found an existing cloud");
+        Ok(ProcessResult::ExistingCloudFound(self))
+    }
+
+    /// Binds the drives found on login, or creates a new cloud and leaves the
+    /// found one untouched on Telegram.
+    pub async fn provision_cloud(mut self, is_reused: bool) -> Result<ProcessResult, ProcessError> {
+        let user = self.get_user()?.clone();
+        if !is_reused {
+            self.drives.clear();
+        }
+
+        if self.find_drive("").is_err() {
+            let chat = self.create_drive_chat(&user, "").await?;
+            self.set_drive(Drive::new(String::new(), chat));
+        }
+        self.select_drive("");
+        self.save_drives()?;
+
+        let session_id = self.session_id()?;
+        Ok(ProcessResult::LoggedIn(self, session_id))
+    }
+
+    fn save_session(&self, session_id: &str) -> Result<(), ProcessError> {
+        let path = Self::session_path(session_id);
+        fs::write(&path, "").map_err(|err| ErrorKind::SessionFileIsNotExist.with_source(err))?;
+        self.tg_client
+            .session()
+            .save_to_file(path)
+            .map_err(|err| ErrorKind::CannotSaveSessionInFile.with_source(err))
+    }
+
+    pub async fn upload_file(
+        self,
+        file: PathBuf,
+        path: Path,
+        mut state: TransferState,
+        throttle: Throttle,
+        on_event: impl Fn(TransferEvent),
+    ) -> Result<(), ProcessError> {
+        let name = file
+            .file_name()
+            .ok_or(ErrorKind::CannotGetFileName)?
+            .to_str()
+            .ok_or(ErrorKind::CannotGetFileName)?
+            .to_string();
+
+        let mut local_file = tokio::fs::File::open(&file)
+            .await
+            .map_err(|err| ErrorKind::CannotReadFile.with_source(err))?;
+        let metadata = local_file
+            .metadata()
+            .await
+            .map_err(|err| ErrorKind::CannotReadFile.with_source(err))?;
+        let file_size = metadata.len();
+        let modified = metadata.modified().ok();
+
+        if state.file_size != file_size
+            || state.modified != modified
+            || state.upload_file_id.is_none()
+        {
+            state.reset(file_size);
+            state.modified = modified;
+        }
+        let file_id = state.upload_file_id.ok_or(ErrorKind::CannotUploadFile)?;
+        let total_parts = state.total_parts();
+        let is_big_file = file_size > BIG_FILE_SIZE;
+        info!(
+            file = %file.display(),
+            file_size,
+            total_parts,
+            confirmed_parts = state.confirmed_parts,
+            "uploading file"
+        );
+
+        // The hashes cover exactly the bytes that were sent, parts confirmed before
+        // a resume are read again only to hash them.
+        let mut hasher = Sha256::new();
+        let mut md5_context = md5::Context::new();
+        for _ in 0..state.confirmed_parts {
+            let bytes = utils::read_part(&mut local_file).await?;
+            hasher.update(&bytes);
+            md5_context.consume(&bytes);
+        }
+
+        while state.confirmed_parts < total_parts {
+            let bytes = utils::read_part(&mut local_file).await?;
+            hasher.update(&bytes);
+            md5_context.consume(&bytes);
+            throttle.wait(bytes.len()).await;
+
+            let mut backoff = Backoff::new();
+            let is_saved = loop {
+                let result = if is_big_file {
+                    self.tg_client
+                        .invoke(&SaveBigFilePart {
+                            file_id,
+                            file_part: state.confirmed_parts,
+                            file_total_parts: total_parts,
+                            bytes: bytes.clone(),
+                        })
+                        .await
+                } else {
+                    self.tg_client
+                        .invoke(&SaveFilePart {
+                            file_id,
+                            file_part: state.confirmed_parts,
+                            bytes: bytes.clone(),
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(v) => break v,
+                    Err(err) => backoff
+                        .wait(err, &on_event)
+                        .await
+                        .map_err(|err| ErrorKind::CannotUploadFile.with_source(err))?,
+                }
+            };
+
+            if !is_saved {
+                return Err(ErrorKind::CannotUploadFile.into());
+            }
+
+            state.confirmed_parts += 1;
+            state.save()?;
+            debug!(part = state.confirmed_parts, total_parts, "uploaded part");
+            on_event(TransferEvent::Progress(state.progress()));
+        }
+
+        let file_metadata = FileMetadata::new(
+            path.join(&name).path(),
+            Some(format!("{:x}", hasher.finalize())),
+        );
+
+        let input_file: tl::enums::InputFile = if is_big_file {
+            tl::types::InputFileBig {
+                id: file_id,
+                parts: total_parts,
+                name,
+            }
+            .into()
+        } else {
+            tl::types::InputFile {
+                id: file_id,
+                parts: total_parts,
+                name,
+                md5_checksum: format!("{:x}", md5_context.compute()),
+            }
+            .into()
+        };
+
+        let text = serde_json::to_string(&file_metadata)
+            .map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+        let chat = self.get_chat()?;
+
+        let mut backoff = Backoff::new();
+        loop {
+            let message =
+                InputMessage::text(&text).document(Uploaded::from_raw(input_file.clone()));
+
+            match self.tg_client.send_message(chat, message).await {
+                Ok(_) => {
+                    state.remove();
+                    info!(file = %file.display(), "uploaded file");
+                    return Ok(());
+                }
+                // Telegram forgot the uploaded parts, so the next attempt has to start over.
+                Err(InvocationError::Rpc(err)) if err.name.starts_with("FILE_PART") => {
+                    warn!(file = %file.display(), "uploaded parts expired: {}", err.name);
+                    state.remove();
+                    return Err(ErrorKind::CannotUploadFile.into());
+                }
+                Err(err) => backoff
+                    .wait(err, &on_event)
+                    .await
+                    .map_err(|err| ErrorKind::MediaMessageIsNotSended.with_source(err))?,
+            }
+        }
+    }
+
+    pub async fn get_uploaded_files(self) -> Result<ProcessResult, ProcessError> {
+        Ok(ProcessResult::UploadedFilesReceived(
+            self.session_id()?,
+            self.drive.clone(),
+            self.list_files().await?,
+        ))
+    }
+
+    async fn list_files(&self) -> Result<Vec<File>, ProcessError> {
+        if self.is_bot() {
+            return self.scan_files().await;
+        }
+
+        let self_id = self.get_user()?.id();
+        let mut files = Vec::<File>::new();
+        let mut messages = self.tg_client.iter_messages(self.get_chat()?);
+
+        loop {
+            let mut backoff = Backoff::new();
+            let message = loop {
+                match messages.next().await {
+                    Ok(v) => break v,
+                    Err(err) => backoff
+                        .wait(err, &|_| {})
+                        .await
+                        .map_err(|err| ErrorKind::CannotReadMessages.with_source(err))?,
+                }
+            };
+            let message = match message {
+                Some(v) => v,
+                None => break,
+            };
+
+            if let Some(file) = Self::file_from_message(&message, self_id) {
+                files.push(file);
+            }
+        }
+        debug!(count = files.len(), "listed uploaded files");
+
+        Ok(files)
+    }
+
+    /// Lists files of a bot by message ids up to the top of the channel, newest first
+    /// like `list_files`, so long runs of deleted messages never end the scan early.
+    async fn scan_files(&self) -> Result<Vec<File>, ProcessError> {
+        let self_id = self.get_user()?.id();
+        let last_id = self.top_message_bound().await?;
+        let mut files = Vec::<File>::new();
+        let mut first_id = 1;
+
+        while first_id <= last_id {
+            let message_ids = (first_id..first_id + BOT_SCAN_BATCH_SIZE).collect::<Vec<i32>>();
+            let messages = self
+                .get_messages_by_id(&message_ids)
+                .await?
+                .flatten()
+                .collect::<Vec<Message>>();
+
+            files.extend(
+                messages
+                    .iter()
+                    .filter_map(|v| Self::file_from_message(v, self_id)),
+            );
+            first_id += BOT_SCAN_BATCH_SIZE;
+        }
+        files.reverse();
+        debug!(count = files.len(), "scanned uploaded files");
+
+        Ok(files)
+    }
+
+    /// An id no message of the drive's channel is above. Bots cannot read the
+    /// history, but every message of a channel raises its `pts`, so it is enough.
+    async fn top_message_bound(&self) -> Result<i32, ProcessError> {
+        let channel = self
+            .get_chat()?
+            .pack()
+            .try_to_input_channel()
+            .ok_or(ErrorKind::StorageChatIsNotChannel)?;
+        let tl::enums::messages::ChatFull::Full(chat_full) = self
+            .tg_client
+            .invoke(&GetFullChannel { channel })
+            .await
+            .map_err(|err| ErrorKind::CannotReadMessages.with_source(err))?;
+
+        match chat_full.full_chat {
+            tl::enums::ChatFull::ChannelFull(v) => Ok(v.pts),
+            tl::enums::ChatFull::Full(_) => Err(ErrorKind::StorageChatIsNotChannel.into()),
+        }
+    }
+
+    /// The file stored in the message. Files posted by another member of a
+    /// shared drive remember who uploaded them.
+    fn file_from_message(message: &Message, self_id: i64) -> Option<File> {
+        let file_metadata = serde_json::from_str::<FileMetadata>(message.text()).ok()?;
+        let mut file = File::new(file_metadata, message.id());
+        file.document_id = match message.media() {
+            Some(Media::Document(document)) => Some(document.id()),
+            Some(Media::Sticker(sticker)) => Some(sticker.document.id()),
+            _ => return None,
+        };
+        file.uploader = message
+            .sender()
+            .filter(|v| v.id() != self_id)
+            .map(|v| v.name().to_string());
+        Some(file)
+    }
+
+    /// Verifies the stored files of every drive, with one report per drive.
+    pub async fn scrub(
+        self,
+        sample_size: Option<usize>,
+        throttle: Throttle,
+    ) -> Vec<Result<ScrubReport, ProcessError>> {
+        let mut reports = Vec::new();
+        for drive in &self.drives {
+            let mut client = self.clone();
+            client.select_drive(&drive.name);
+            reports.push(client.scrub_drive(sample_size, throttle.clone()).await);
+        }
+        // A drive whose chat is gone cannot be checked at all.
+        for name in &self.missing_drives {
+            reports.push(Err(ProcessError::from(ErrorKind::CloudChatIsNotFound)
+                .with_operation("Scrub")
+                .with_path(Drive::label(name))));
+        }
+
+        reports
+    }
+
+    /// Checks a sample of `sample_size` files, or all files, of the selected drive.
+    async fn scrub_drive(
+        self,
+        sample_size: Option<usize>,
+        throttle: Throttle,
+    ) -> Result<ScrubReport, ProcessError> {
+        let client_name = self.session_id()?;
+        let mut files = self
+            .list_files()
+            .await
+            .map_err(|err| err.with_operation("Scrub").with_path(Drive::label(&self.drive)))?;
+        let total_files = files.len();
+
+        if let Some(sample_size) = sample_size {
+            files.shuffle(&mut rand::thread_rng());
+            files.truncate(sample_size);
+        }
+
+        let mut report = ScrubReport::new(self.display_name(), self.drive.clone(), total_files);
+        info!(
+            client = %client_name,
+            drive = %self.drive,
+            total_files,
+            checked = files.len(),
+            "scrubbing files"
+        );
+
+        for file in files {
+            let path = file.path.path();
+            let is_hashed = file.hash.is_some();
+            let state = TransferState::load_or_new(
+                &client_name,
+                &self.drive,
+                &JobKind::Verify(file.clone()),
+            );
+
+            match self
+                .clone()
+                .verify_file(file, state, throttle.clone(), |_| {})
+                .await
+            {
+                Ok(_) if is_hashed => report.checked_files += 1,
+                Ok(_) => report.unhashed_files += 1,
+                Err(err) if err.kind == ErrorKind::HashMismatch => {
+                    report.checked_files += 1;
+                    report.mismatched.push(path);
+                }
+                Err(err) => report.unreadable.push((path, err.to_string())),
+            }
+        }
+        report.finish();
+        info!("{}", report);
+
+        Ok(report)
+    }
+
+    async fn get_document(&self, message_id: i32) -> Result<(Media, String, u64), ProcessError> {
+        let message = self
+            .get_messages_by_id(&[message_id])
+            .await?
+            .next()
+            .flatten()
+            .ok_or(ErrorKind::MessagesNotFound)?;
+
+        let media = message.media().ok_or(ErrorKind::MessageNotContainsMedia)?;
+        let document = match &media {
+            Media::Document(document) => document,
+            Media::Sticker(sticker) => &sticker.document,
+            _ => return Err(ErrorKind::MessageNotContainsMedia.into()),
+        };
+        let name = document.name().to_string();
+        let size = document.size() as u64;
+
+        Ok((media, name, size))
+    }
+
+    pub async fn download_file(
+        self,
+        file: File,
+        settings: Settings,
+        state: TransferState,
+        throttle: Throttle,
+        on_event: impl Fn(TransferEvent),
+    ) -> Result<(), ProcessError> {
+        let (media, name, size) = self.get_document(file.message_id).await?;
+        tokio::fs::create_dir_all(&settings.download_directory)
+            .await
+            .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+        // Parts are keyed by the transfer, files of the same name never share them.
+        let part_path = settings.download_path(&format!("{}.part", state.key()));
+
+        self.download_media(media, size, &part_path, state, throttle, on_event)
+            .await?;
+
+        let output_path = utils::reserve_path(&settings.download_path(&name)).await?;
+        tokio::fs::rename(&part_path, &output_path)
+            .await
+            .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+        info!(output = output_path, "downloaded file");
+
+        if let Err(err) = utils::check_hash(&output_path, &file.hash).await {
+            warn!(path = %file.path.path(), "downloaded file does not match its hash");
+            // Keep the corrupted bytes for inspection, but never under the real name.
+            let _ = tokio::fs::rename(&output_path, format!("{}.corrupted", output_path)).await;
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the file to a temporary location and compares it with the hash
+    /// recorded at upload time. Files without a hash are only checked to be readable.
+    pub async fn verify_file(
+        self,
+        file: File,
+        state: TransferState,
+        throttle: Throttle,
+        on_event: impl Fn(TransferEvent),
+    ) -> Result<(), ProcessError> {
+        let (media, _, size) = self.get_document(file.message_id).await?;
+        // The key covers the client and the drive, message ids repeat between chats.
+        let output_path = std::env::temp_dir()
+            .join(format!("tucha-verify-{}.part", state.key()))
+            .display()
+            .to_string();
+
+        self.download_media(media, size, &output_path, state, throttle, on_event)
+            .await?;
+
+        let result = utils::check_hash(&output_path, &file.hash).await;
+        let _ = tokio::fs::remove_file(&output_path).await;
+        result
+    }
+
+    /// Downloads the media into `part_path`, continuing from the parts saved in `state`.
+    async fn download_media(
+        &self,
+        media: Media,
+        size: u64,
+        part_path: &str,
+        mut state: TransferState,
+        throttle: Throttle,
+        on_event: impl Fn(TransferEvent),
+    ) -> Result<(), ProcessError> {
+
+        let mut part_file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&part_path)
+            .await
+            .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+        let part_file_size = part_file
+            .metadata()
+            .await
+            .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?
+            .len();
+        let confirmed_size = state.confirmed_parts as u64 * PART_SIZE as u64;
+
+        if state.file_size != size || part_file_size < confirmed_size {
+            state.reset(size);
+        }
+        let confirmed_size = state.confirmed_parts as u64 * PART_SIZE as u64;
+        info!(
+            output = part_path,
+            size,
+            confirmed_parts = state.confirmed_parts,
+            "downloading file"
+        );
+
+        part_file
+            .set_len(confirmed_size)
+            .await
+            .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+        part_file
+            .seek(SeekFrom::Start(confirmed_size))
+            .await
+            .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+
+        let mut download = self
+            .tg_client
+            .iter_download(&Downloadable::Media(media))
+            .chunk_size(PART_SIZE as i32)
+            .skip_chunks(state.confirmed_parts);
+
+        loop {
+            let mut backoff = Backoff::new();
+            let chunk = loop {
+                match download.next().await {
+                    Ok(v) => break v,
+                    Err(err) => backoff
+                        .wait(err, &on_event)
+                        .await
+                        .map_err(|err| ErrorKind::CannotDownloadMedia.with_source(err))?,
+                }
+            };
+            let chunk = match chunk {
+                Some(v) => v,
+                None => break,
+            };
+            throttle.wait(chunk.len()).await;
+
+            part_file
+                .write_all(&chunk)
+                .await
+                .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+            part_file
+                .flush()
+                .await
+                .map_err(|err| ErrorKind::CannotWriteFile.with_source(err))?;
+
+            state.confirmed_parts += 1;
+            state.save()?;
+            debug!(part = state.confirmed_parts, "downloaded part");
+            on_event(TransferEvent::Progress(state.progress()));
+        }
+
+        state.remove();
+
+        Ok(())
+    }
+
+    pub async fn delete_files(self, files: Vec<File>) -> Result<ProcessResult, ProcessError> {
+        let message_ids = files.iter().map(|file| file.message_id).collect::<Vec<i32>>();
+        let messages = self.get_messages_by_id(&message_ids).await?;
+        info!(count = message_ids.len(), "deleting files");
+
+        let mut results = Vec::new();
+        for (file, message) in files.into_iter().zip(messages) {
+            let result = match message {
+                Some(message) => message
+                    .delete()
+                    .await
+                    .map_err(|err| ErrorKind::CannotDeleteFile.with_source(err)),
+                None => Err(ErrorKind::MessagesNotFound.into()),
+            };
+            let result = result.map_err(|err: ProcessError| {
+                err.with_operation("Delete").with_path(file.path.path())
+            });
+            results.push((file, result));
+        }
+
+        Ok(ProcessResult::FilesDeleted(results))
+    }
+
+    /// Copies the files into `destination` by sending their documents again by
+    /// reference, nothing is downloaded or uploaded. Paths below `source` are kept,
+    /// so copying a directory copies its whole subtree.
+    pub async fn copy_files(
+        self,
+        files: Vec<File>,
+        source: Path,
+        destination: Path,
+    ) -> Result<ProcessResult, ProcessError> {
+        let message_ids = files.iter().map(|file| file.message_id).collect::<Vec<i32>>();
+        let messages = self.get_messages_by_id(&message_ids).await?;
+        let chat = self.get_chat()?;
+        info!(count = message_ids.len(), destination = %destination.path(), "copying files");
+
+        let mut results = Vec::new();
+        for (file, message) in files.into_iter().zip(messages) {
+            let file_metadata = Self::copy_metadata(&file, &source, &destination);
+
+            let result = match message.as_ref().and_then(|v| v.media()) {
+                Some(media) => self.send_media_copy(chat, &media, &file_metadata).await,
+                None => Err(ErrorKind::MessagesNotFound.into()),
+            };
+            let result = result.map_err(|err: ProcessError| {
+                err.with_operation("Copy").with_path(file.path.path())
+            });
+            results.push((file, result));
+        }
+
+        Ok(ProcessResult::FilesCopied(results))
+    }
+
+    /// Metadata of the copy of `file` in `destination`, keeping its path below `source`.
+    fn copy_metadata(file: &File, source: &Path, destination: &Path) -> FileMetadata {
+        let relative_path = file
+            .path
+            .components()
+            .into_iter()
+            .skip(source.components().len())
+            .collect::<Vec<String>>()
+            .join("/");
+        FileMetadata::new(destination.join(&relative_path).path(), file.hash.clone())
+    }
+
+    /// Copies or moves the files into the current drive of `target`, which is
+    /// another account or another drive of this one, without downloading them.
+    ///
+    /// Another account cannot read this cloud chat, so the messages are first
+    /// forwarded to it in a private chat, sent from there to its cloud chat by
+    /// reference, and then deleted from the private chat.
+    pub async fn transfer_files(
+        self,
+        target: Client,
+        files: Vec<File>,
+        source: Path,
+        destination: Path,
+        is_move: bool,
+        on_progress: impl Fn(usize, usize),
+    ) -> Result<ProcessResult, ProcessError> {
+        let is_same_account = self.session_id()? == target.session_id()?;
+        if is_same_account && self.drive == target.drive {
+            return Err(ErrorKind::TransferTargetIsSource.into());
+        }
+        let relay_peer = match is_same_account {
+            true => None,
+            false => Some(self.resolve_account(target.get_user()?).await?),
+        };
+        info!(
+            count = files.len(),
+            target = %target.session_id()?,
+            drive = %target.drive,
+            is_move,
+            "transferring files"
+        );
+
+        let total = files.len();
+        let mut relay_chat = None;
+        let mut results = Vec::new();
+        on_progress(0, total);
+
+        for batch in files.chunks(TRANSFER_BATCH_SIZE) {
+            let relay_chat = &mut relay_chat;
+            let mut batch_results = self
+                .transfer_batch(&target, relay_peer, relay_chat, batch, &source, &destination)
+                .await;
+
+            if is_move {
+                self.delete_transferred(&mut batch_results).await;
+            }
+            results.extend(batch_results);
+            on_progress(results.len(), total);
+        }
+
+        Ok(ProcessResult::FilesTransferred(target.session_id()?, results))
+    }
+
+    /// Copies up to `TRANSFER_BATCH_SIZE` files to `target`, relaying them through
+    /// `relay_peer` when `target` is another account.
+    async fn transfer_batch(
+        &self,
+        target: &Client,
+        relay_peer: Option<PackedChat>,
+        relay_chat: &mut Option<Chat>,
+        files: &[File],
+        source: &Path,
+        destination: &Path,
+    ) -> Vec<(File, Result<(), ProcessError>)> {
+        let results = match relay_peer {
+            Some(relay_peer) => {
+                self.relay_batch(target, relay_peer, relay_chat, files, source, destination)
+                    .await
+            }
+            None => self.copy_batch(target, files, source, destination).await,
+        };
+
+        files
+            .iter()
+            .cloned()
+            .zip(results)
+            .map(|(file, result)| {
+                let result = result.map_err(|err: ProcessError| {
+                    err.with_operation("Transfer").with_path(file.path.path())
+                });
+                (file, result)
+            })
+            .collect()
+    }
+
+    /// Copies every file of every drive to the drive of the same name of
+    /// `target`, creating the drives it lacks, and then checks that a file of
+    /// the same path and hash arrived for every source file. Directories only
+    /// exist as parts of file paths, and every version of a path is a file of
+    /// its own, so both are carried over with the files.
+    pub async fn migrate(
+        self,
+        mut target: Client,
+        on_progress: impl Fn(usize, usize),
+    ) -> Result<ProcessResult, ProcessError> {
+        let source_client = self.session_id()?;
+        let target_client = target.session_id()?;
+        if source_client == target_client {
+            return Err(ErrorKind::TransferTargetIsSource.into());
+        }
+        let relay_peer = Some(self.resolve_account(target.get_user()?).await?);
+        let mut relay_chat = None;
+
+        let mut migration = Migration::load_or_new(&source_client, &target_client);
+        let mut drives = Vec::new();
+        for drive in &self.drives {
+            let mut source = self.clone();
+            source.select_drive(&drive.name);
+            let files = source.list_files().await?;
+            drives.push((source, files));
+        }
+        let total = drives.iter().map(|(_, files)| files.len()).sum::<usize>();
+        info!(
+            source = %source_client,
+            target = %target_client,
+            total,
+            resumed = migration.migrated_files(),
+            "migrating cloud"
+        );
+        on_progress(migration.migrated_files(), total);
+
+        for (source, files) in &drives {
+            let name = source.drive.clone();
+            if !target.select_drive(&name) {
+                let user = target.get_user()?.clone();
+                if user.is_bot() {
+                    return Err(ErrorKind::BotCannotCreateChat.into());
+                }
+                let chat = target.create_drive_chat(&user, &name).await?;
+                target.set_drive(Drive::new(name.clone(), chat));
+                target.save_drives()?;
+                target.select_drive(&name);
+            }
+
+            let pending = {
+                let state = migration.drives.entry(name.clone()).or_default();
+                state.source_files = files.len();
+                files
+                    .iter()
+                    .filter(|v| !state.migrated.contains(&v.message_id))
+                    .cloned()
+                    .collect::<Vec<File>>()
+            };
+
+            for batch in pending.chunks(TRANSFER_BATCH_SIZE) {
+                let root = Path::default();
+                let relay_chat = &mut relay_chat;
+                let results = source
+                    .transfer_batch(&target, relay_peer, relay_chat, batch, &root, &root)
+                    .await;
+
+                let state = migration.drives.entry(name.clone()).or_default();
+                for (file, result) in results {
+                    match result {
+                        Ok(()) => {
+                            state.migrated.insert(file.message_id);
+                            state.failed.remove(&file.message_id);
+                        }
+                        Err(err) => {
+                            warn!("{}", err);
+                            state.failed.insert(file.message_id, err.to_string());
+                        }
+                    }
+                }
+                migration.save()?;
+                on_progress(migration.migrated_files(), total);
+            }
+
+            let target_files = target.list_files().await?;
+            let state = migration.drives.entry(name.clone()).or_default();
+            Self::verify_migration(state, files, &target_files);
+        }
+
+        migration.finish();
+        migration.save()?;
+        info!(verified = migration.is_verified(), "migration finished");
+
+        Ok(ProcessResult::MigrationFinished(migration))
+    }
+
+    /// Counts the files of both drives and looks for a target file of the same
+    /// path and document for every source file. Copies keep the id of the
+    /// document, so a copy of the wrong one or a message without it never matches.
+    /// Target files left over after every source file found its copy are
+    /// duplicates, e.g. of a batch sent again after an interruption.
+    fn verify_migration(state: &mut DriveMigration, source_files: &[File], target_files: &[File]) {
+        let mut target_paths = BTreeMap::<(String, Option<i64>), usize>::new();
+        for file in target_files {
+            *target_paths
+                .entry((file.path.path(), file.document_id))
+                .or_default() += 1;
+        }
+
+        state.source_files = source_files.len();
+        state.target_files = target_files.len();
+        state.missing.clear();
+        state.duplicates.clear();
+        let mut source_keys = BTreeSet::new();
+        for file in source_files {
+            let key = (file.path.path(), file.document_id);
+            match target_paths.get_mut(&key) {
+                Some(count) if *count > 0 && file.document_id.is_some() => *count -= 1,
+                _ => state.missing.push(file.path.path()),
+            }
+            source_keys.insert(key);
+        }
+        for (key, count) in target_paths {
+            if source_keys.contains(&key) {
+                state.duplicates.extend(std::iter::repeat_n(key.0, count));
+            }
+        }
+    }
+
+    /// The user of another account as seen by this one, found by its username
+    /// or its phone number.
+    async fn resolve_account(&self, user: &User) -> Result<PackedChat, ProcessError> {
+        if let Some(username) = user.username() {
+            if let Ok(Some(chat)) = self.tg_client.resolve_username(username).await {
+                return Ok(chat.pack());
+            }
+        }
+
+        if let Some(phone) = user.phone() {
+            let resolved = self
+                .tg_client
+                .invoke(&ResolvePhone {
+                    phone: phone.to_string(),
+                })
+                .await;
+            if let Ok(tl::enums::contacts::ResolvedPeer::Peer(resolved)) = resolved {
+                for resolved_user in resolved.users {
+                    match resolved_user {
+                        tl::enums::User::User(v) if v.id == user.id() => {
+                            return Ok(PackedChat {
+                                ty: if v.bot { PackedType::Bot } else { PackedType::User },
+                                id: v.id,
+                                access_hash: v.access_hash,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Err(ErrorKind::AccountIsNotReachable.into())
+    }
+
+    /// Sends the files to another drive of the same account by reference.
+    async fn copy_batch(
+        &self,
+        target: &Client,
+        files: &[File],
+        source: &Path,
+        destination: &Path,
+    ) -> Vec<Result<(), ProcessError>> {
+        let message_ids = files.iter().map(|file| file.message_id).collect::<Vec<i32>>();
+        let messages = self.get_messages_by_id(&message_ids).await;
+        let (messages, target_chat) = match (messages, target.get_chat()) {
+            (Ok(messages), Ok(chat)) => (messages, chat),
+            (Err(err), _) | (_, Err(err)) => return vec![Err(err); files.len()],
+        };
+
+        let mut results = Vec::new();
+        for (file, message) in files.iter().zip(messages) {
+            let file_metadata = Self::copy_metadata(file, source, destination);
+            let result = match message.as_ref().and_then(|v| v.media()) {
+                Some(media) => target.send_media_copy(target_chat, &media, &file_metadata).await,
+                None => Err(ErrorKind::MessagesNotFound.into()),
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Forwards the files to the user of `target` and sends them from that
+    /// private chat to its cloud chat, matching the messages by document id.
+    async fn relay_batch(
+        &self,
+        target: &Client,
+        relay_peer: PackedChat,
+        relay_chat: &mut Option<Chat>,
+        files: &[File],
+        source: &Path,
+        destination: &Path,
+    ) -> Vec<Result<(), ProcessError>> {
+        match self
+            .try_relay_batch(target, relay_peer, relay_chat, files, source, destination)
+            .await
+        {
+            Ok(v) => v,
+            Err(err) => vec![Err(err); files.len()],
+        }
+    }
+
+    async fn try_relay_batch(
+        &self,
+        target: &Client,
+        relay_peer: PackedChat,
+        relay_chat: &mut Option<Chat>,
+        files: &[File],
+        source: &Path,
+        destination: &Path,
+    ) -> Result<Vec<Result<(), ProcessError>>, ProcessError> {
+        let message_ids = files.iter().map(|file| file.message_id).collect::<Vec<i32>>();
+        let document_ids = self
+            .get_messages_by_id(&message_ids)
+            .await?
+            .map(|v| v.and_then(|v| v.media()).and_then(|v| document_id(&v)))
+            .collect::<Vec<Option<i64>>>();
+        let forwarded_ids = files
+            .iter()
+            .zip(&document_ids)
+            .filter(|(_, document_id)| document_id.is_some())
+            .map(|(file, _)| file.message_id)
+            .collect::<Vec<i32>>();
+
+        let forwarded_count = self
+            .tg_client
+            .forward_messages(relay_peer, &forwarded_ids, self.get_chat()?)
+            .await
+            .map_err(|err| ErrorKind::CannotTransferFile.with_source(err))?
+            .len();
+
+        if relay_chat.is_none() {
+            *relay_chat = Client::find_dialog(&target.tg_client, self.get_user()?.id()).await?;
+        }
+        let relay_chat = relay_chat.as_ref().ok_or(ErrorKind::AccountIsNotReachable)?;
+
+        let mut relayed_messages = BTreeMap::<i64, Message>::new();
+        let mut messages = target.tg_client.iter_messages(relay_chat).limit(forwarded_count);
+        while let Some(message) = messages
+            .next()
+            .await
+            .map_err(|err| ErrorKind::CannotReadMessages.with_source(err))?
+        {
+            if let Some(document_id) = message.media().and_then(|v| document_id(&v)) {
+                relayed_messages.entry(document_id).or_insert(message);
+            }
+        }
+
+        let target_chat = target.get_chat()?;
+        let mut results = Vec::new();
+        for (file, document_id) in files.iter().zip(document_ids) {
+            let file_metadata = Self::copy_metadata(file, source, destination);
+            let media = document_id
+                .and_then(|v| relayed_messages.get(&v))
+                .and_then(|v| v.media());
+            let result = match media {
+                Some(media) => target.send_media_copy(target_chat, &media, &file_metadata).await,
+                None => Err(ErrorKind::MessagesNotFound.into()),
+            };
+            results.push(result);
+        }
+
+        let relayed_ids = relayed_messages.values().map(|v| v.id()).collect::<Vec<i32>>();
+        if let Err(err) = target.tg_client.delete_messages(relay_chat, &relayed_ids).await {
+            warn!("cannot delete relayed messages: {}", err);
+        }
+
+        Ok(results)
+    }
+
+    /// Deletes the source messages of the files that were transferred.
+    async fn delete_transferred(&self, results: &mut [(File, Result<(), ProcessError>)]) {
+        let message_ids = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(file, _)| file.message_id)
+            .collect::<Vec<i32>>();
+        if message_ids.is_empty() {
+            return;
+        }
+
+        let deleted = match self.get_chat() {
+            Ok(chat) => self
+                .tg_client
+                .delete_messages(chat, &message_ids)
+                .await
+                .map_err(|err| ErrorKind::CannotDeleteFile.with_source(err)),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = deleted {
+            for (file, result) in results.iter_mut().filter(|(_, result)| result.is_ok()) {
+                *result = Err(err.clone().with_operation("Delete").with_path(file.path.path()));
+            }
+        }
+    }
+
+    /// Sends `media` to `chat` again by reference, described by `file_metadata`.
+    async fn send_media_copy(
+        &self,
+        chat: &Chat,
+        media: &Media,
+        file_metadata: &FileMetadata,
+    ) -> Result<(), ProcessError> {
+        let text = serde_json::to_string(file_metadata)
+            .map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+
+        let mut backoff = Backoff::new();
+        loop {
+            let message = InputMessage::text(&text).copy_media(media);
+            match self.tg_client.send_message(chat, message).await {
+                Ok(_) => {
+                    debug!(path = %file_metadata.path, "copied file");
+                    return Ok(());
+                }
+                Err(err) => backoff
+                    .wait(err, &|_| {})
+                    .await
+                    .map_err(|err| ErrorKind::CannotCopyFile.with_source(err))?,
+            }
+        }
+    }
+}
+
+/// Id of the document stored in the media, the same for every account.
+fn document_id(media: &Media) -> Option<i64> {
+    match media {
+        Media::Document(v) => Some(v.id()),
+        Media::Sticker(v) => Some(v.document.id()),
+        _ => None,
+    }
+}
+
+/// RPC errors meaning that the session can no longer be used and the account has to log in again.
+fn is_authorization_lost(name: &str) -> bool {
+    matches!(
+        name,
+        "AUTH_KEY_UNREGISTERED"
+            | "AUTH_KEY_INVALID"
+            | "SESSION_REVOKED"
+            | "SESSION_EXPIRED"
+            | "USER_DEACTIVATED"
+            | "USER_DEACTIVATED_BAN"
+    )
+}
+
+/// grammers only speaks SOCKS5, MTProxy links are rejected with a clear error
+/// instead of failing somewhere inside the connection.
+fn check_proxy_url(proxy_url: &str) -> Result<(), ProcessError> {
+    let proxy_url = proxy_url.to_lowercase();
+    if proxy_url.starts_with("socks5://") {
+        Ok(())
+    } else if proxy_url.starts_with("tg://proxy")
+        || proxy_url.contains("t.me/proxy")
+        || proxy_url.contains("secret=")
+    {
+        Err(ErrorKind::MTProxyIsNotSupported.into())
+    } else {
+        Err(ErrorKind::UnsupportedProxy.into())
+    }
+}
+
+fn sign_in_error(err: SignInError) -> ProcessError {
+    match err {
+        SignInError::PasswordRequired(_) => ErrorKind::PasswordRequired.into(),
+        SignInError::SignUpRequired { .. } => ErrorKind::SignUpRequired.into(),
+        SignInError::InvalidCode => ErrorKind::InvalidCode.into(),
+        SignInError::InvalidPassword => ErrorKind::InvalidPassword.into(),
+        err => ErrorKind::OtherSignInError.with_source(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(message_id: i32, path: &str, document_id: Option<i64>) -> File {
+        let mut file = File::new(FileMetadata::new(path.to_string(), None), message_id);
+        file.document_id = document_id;
+        file
+    }
+
+    fn verify(source_files: &[File], target_files: &[File]) -> DriveMigration {
+        let mut state = DriveMigration::default();
+        Client::verify_migration(&mut state, source_files, target_files);
+        state
+    }
+
+    #[test]
+    fn matching_files_are_verified() {
+        let source = [file(1, "/a.txt", Some(10)), file(2, "/b.txt", Some(20))];
+        let target = [file(7, "/b.txt", Some(20)), file(8, "/a.txt", Some(10))];
+
+        let state = verify(&source, &target);
+        assert!(state.missing.is_empty());
+        assert!(state.duplicates.is_empty());
+        assert_eq!((state.source_files, state.target_files), (2, 2));
+        assert!(state.is_verified());
+    }
+
+    #[test]
+    fn file_with_another_document_is_missing() {
+        let source = [file(1, "/a.txt", Some(10))];
+        let target = [file(7, "/a.txt", Some(11))];
+
+        assert_eq!(verify(&source, &target).missing, ["/a.txt"]);
+    }
+
+    #[test]
+    fn file_without_document_is_missing() {
+        let source = [file(1, "/a.txt", None)];
+        let target = [file(7, "/a.txt", None)];
+
+        assert_eq!(verify(&source, &target).missing, ["/a.txt"]);
+    }
+
+    #[test]
+    fn same_document_needs_a_copy_each() {
+        let source = [file(1, "/a.txt", Some(10)), file(2, "/a.txt", Some(10))];
+        let target = [file(7, "/a.txt", Some(10))];
+
+        let state = verify(&source, &target);
+        assert_eq!(state.missing, ["/a.txt"]);
+        assert!(!state.is_verified());
+    }
+
+    #[test]
+    fn files_sent_again_are_duplicates() {
+        let source = [file(1, "/a.txt", Some(10)), file(2, "/b.txt", Some(20))];
+        let target = [
+            file(7, "/a.txt", Some(10)),
+            file(8, "/a.txt", Some(10)),
+            file(9, "/b.txt", Some(20)),
+        ];
+
+        let state = verify(&source, &target);
+        assert!(state.missing.is_empty());
+        assert_eq!(state.duplicates, ["/a.txt"]);
+    }
+
+    #[test]
+    fn other_target_files_are_counted_only() {
+        let source = [file(1, "/a.txt", Some(10))];
+        let target = [file(7, "/a.txt", Some(10)), file(8, "/old.txt", Some(30))];
+
+        let state = verify(&source, &target);
+        assert!(state.missing.is_empty());
+        assert!(state.duplicates.is_empty());
+        assert_eq!(state.target_files, 2);
+    }
+
+    #[test]
+    fn verifying_again_replaces_the_results() {
+        let source = [file(1, "/a.txt", Some(10))];
+        let mut state = verify(&source, &[]);
+        assert_eq!(state.missing, ["/a.txt"]);
 
-        Ok(ProcessResult::FilesDeleted)
+        Client::verify_migration(&mut state, &source, &[file(7, "/a.txt", Some(10))]);
+        assert!(state.missing.is_empty());
     }
 }
@@ -31,7 +31,7 @@ impl Dir {
 
     pub fn add_new_path(&mut self, mut components: IntoIter<String>) -> &mut Self {
         let mut rel_dir = self;
-        while let Some(name) = components.next() {
+        for name in components.by_ref() {
             rel_dir = rel_dir
                 .children_dirs
                 .entry(name.to_string())
@@ -48,7 +48,7 @@ impl Dir {
         let mut components = path.components().into_iter();
 
         let mut relative_dir = self;
-        while let Some(name) = components.next() {
+        for name in components.by_ref() {
             if let Some(next_dir) = relative_dir.find_mut_child(&name) {
                 relative_dir = next_dir;
                 continue;
@@ -62,7 +62,11 @@ impl Dir {
         &self.children_dirs
     }
 
-    pub fn get_files_messages_ids(&self) -> Vec<i32> {
-        self.files.iter().map(|file| file.message_id).collect()
+    pub fn get_files_recursively(&self) -> Vec<File> {
+        let mut files = self.files.clone();
+        for dir in self.children_dirs.values() {
+            files.extend(dir.get_files_recursively());
+        }
+        files
     }
 }
@@ -0,0 +1,113 @@
+use grammers_client::types::Chat;
+
+/// A group or channel files are stored in. Every account has the main drive
+/// and can have more named ones, each in its own chat.
+#[derive(Clone, Debug)]
+pub struct Drive {
+    /// Empty for the main drive.
+    pub name: String,
+    pub chat: Chat,
+}
+
+impl Drive {
+    pub fn new(name: String, chat: Chat) -> Self {
+        Self { name, chat }
+    }
+
+    /// Title of the chat of a drive: `TuchaCloud-<user id>` for the main one,
+    /// `TuchaCloud-<user id>: <name>` for the others.
+    pub fn title(user_id: i64, name: &str) -> String {
+        if name.is_empty() {
+            format!("TuchaCloud-{}", user_id)
+        } else {
+            format!("TuchaCloud-{}: {}", user_id, name)
+        }
+    }
+
+    /// The drive stored in `chat`, if its title is one of the titles above.
+    pub fn from_chat(user_id: i64, chat: &Chat) -> Option<Self> {
+        let title = chat.name();
+        let prefix = Self::title(user_id, "");
+        let rest = title.strip_prefix(&prefix)?;
+
+        let name = if rest.is_empty() {
+            String::new()
+        } else {
+            rest.strip_prefix(": ")?.trim().to_string()
+        };
+        Some(Self::new(name, chat.clone()))
+    }
+
+    pub fn label(name: &str) -> &str {
+        if name.is_empty() {
+            "Main"
+        } else {
+            name
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grammers_client::types::chat::Group;
+    use grammers_tl_types as tl;
+
+    use super::*;
+
+    const USER_ID: i64 = 42;
+
+    fn chat(title: &str) -> Chat {
+        Chat::Group(Group::from_raw(
+            tl::types::ChatForbidden {
+                id: 1,
+                title: title.to_string(),
+            }
+            .into(),
+        ))
+    }
+
+    #[test]
+    fn titles_of_main_and_named_drives() {
+        assert_eq!(Drive::title(USER_ID, ""), "TuchaCloud-42");
+        assert_eq!(Drive::title(USER_ID, "Photos"), "TuchaCloud-42: Photos");
+    }
+
+    #[test]
+    fn main_drive_from_its_title() {
+        let drive = Drive::from_chat(USER_ID, &chat("TuchaCloud-42")).unwrap();
+        assert_eq!(drive.name, "");
+    }
+
+    #[test]
+    fn named_drive_from_its_title() {
+        let drive = Drive::from_chat(USER_ID, &chat("TuchaCloud-42: Photos")).unwrap();
+        assert_eq!(drive.name, "Photos");
+    }
+
+    #[test]
+    fn title_round_trips() {
+        for name in ["", "Photos", "Work: 2024"] {
+            let drive = Drive::from_chat(USER_ID, &chat(&Drive::title(USER_ID, name))).unwrap();
+            assert_eq!(drive.name, name);
+        }
+    }
+
+    #[test]
+    fn drives_of_other_users_are_skipped() {
+        assert!(Drive::from_chat(USER_ID, &chat("TuchaCloud-7")).is_none());
+        assert!(Drive::from_chat(USER_ID, &chat("TuchaCloud-420")).is_none());
+        assert!(Drive::from_chat(USER_ID, &chat("TuchaCloud-420: Photos")).is_none());
+    }
+
+    #[test]
+    fn lookalike_titles_are_skipped() {
+        assert!(Drive::from_chat(USER_ID, &chat("x TuchaCloud-42")).is_none());
+        assert!(Drive::from_chat(USER_ID, &chat("x TuchaCloud-42: Photos")).is_none());
+    }
+
+    #[test]
+    fn other_chats_are_skipped() {
+        assert!(Drive::from_chat(USER_ID, &chat("Holidays")).is_none());
+        assert!(Drive::from_chat(USER_ID, &chat("TuchaCloud-42 Photos")).is_none());
+    }
+}
@@ -1,9 +1,17 @@
+use serde::{Deserialize, Serialize};
+
 use super::{FileMetadata, Path};
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct File {
     pub path: Path,
-    pub message_id: i32
+    pub message_id: i32,
+    pub hash: Option<String>,
+    /// Name of the member who uploaded the file to a shared drive.
+    pub uploader: Option<String>,
+    /// Id of the Telegram document, the same in every copy sent by reference.
+    #[serde(skip)]
+    pub document_id: Option<i64>,
 }
 
 impl File {
@@ -11,6 +19,9 @@ impl File {
         Self {
             path: Path::from(metadata.path), 
             message_id,
+            hash: metadata.hash,
+            uploader: None,
+            document_id: None,
         }
     }
 }
\ No newline at end of file
@@ -3,12 +3,16 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileMetadata {
     pub path: String,
+    /// SHA-256 of the content, absent in files uploaded before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
 }
 
 impl FileMetadata {
-    pub fn new(path: String) -> Self {
+    pub fn new(path: String, hash: Option<String>) -> Self {
         Self {
-            path
+            path,
+            hash,
         }
     }
 }
@@ -0,0 +1,80 @@
+use std::{
+    sync::{atomic::AtomicU64, Arc},
+    time::Instant,
+};
+
+use tokio::task::AbortHandle;
+
+use crate::enums::{JobKind, JobStatus, TransferEvent};
+
+#[derive(Debug)]
+pub struct Job {
+    pub id: usize,
+    pub client_name: String,
+    /// Drive of the client the transfer belongs to, empty for the main one.
+    pub drive: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub progress: f32,
+    /// Per job rate in bytes per second, `0` means unlimited.
+    pub rate_limit: Arc<AtomicU64>,
+    /// Failed on a dropped connection and resumes once the client reconnects.
+    pub awaiting_reconnect: bool,
+    /// Times the job was resumed after a reconnect.
+    pub reconnects: u32,
+    abort_handle: Option<AbortHandle>,
+}
+
+impl Job {
+    pub fn new(id: usize, client_name: String, drive: String, kind: JobKind) -> Self {
+        Self {
+            id,
+            client_name,
+            drive,
+            kind,
+            status: JobStatus::Queued,
+            progress: 0.0,
+            rate_limit: Arc::new(AtomicU64::new(0)),
+            awaiting_reconnect: false,
+            reconnects: 0,
+            abort_handle: None,
+        }
+    }
+
+    pub fn transfer_key(&self) -> String {
+        self.kind.transfer_key(&self.client_name, &self.drive)
+    }
+
+    pub fn set_running(&mut self, abort_handle: AbortHandle) {
+        self.status = JobStatus::Running;
+        self.awaiting_reconnect = false;
+        self.abort_handle = Some(abort_handle);
+    }
+
+    pub fn apply_event(&mut self, event: TransferEvent) {
+        match event {
+            TransferEvent::Progress(progress) => {
+                self.status = JobStatus::Running;
+                self.progress = progress;
+            }
+            TransferEvent::Waiting(delay, reason) => {
+                self.status = JobStatus::Waiting {
+                    until: Instant::now() + delay,
+                    reason,
+                };
+            }
+        }
+    }
+
+    pub fn set_finished(&mut self, status: JobStatus) {
+        self.status = status;
+        self.abort_handle = None;
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(abort_handle) = self.abort_handle.take() {
+            abort_handle.abort();
+        }
+        self.status = JobStatus::Paused;
+    }
+}
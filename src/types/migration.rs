@@ -0,0 +1,114 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs,
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::enums::ErrorKind;
+
+use super::ProcessError;
+
+const MIGRATIONS_DIRECTORY: &str = "./migrations";
+
+/// Copy of a whole cloud to another account, drive by drive. Saved after
+/// every batch, so an interrupted migration continues where it stopped, and
+/// kept afterwards as its report.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Migration {
+    pub source_client: String,
+    pub target_client: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Progress and verification of every drive, by drive name.
+    pub drives: BTreeMap<String, DriveMigration>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DriveMigration {
+    /// Message ids of the source files already copied.
+    pub migrated: BTreeSet<i32>,
+    /// Reasons the source files could not be copied, by message id.
+    pub failed: BTreeMap<i32, String>,
+    pub source_files: usize,
+    pub target_files: usize,
+    /// Source files without a file of the same path and document in the target drive.
+    pub missing: Vec<String>,
+    /// Source files copied more than once, once per extra copy.
+    pub duplicates: Vec<String>,
+}
+
+impl Migration {
+    fn new(source_client: &str, target_client: &str) -> Self {
+        Self {
+            source_client: source_client.to_string(),
+            target_client: target_client.to_string(),
+            started_at: Local::now().to_rfc3339(),
+            finished_at: None,
+            drives: BTreeMap::new(),
+        }
+    }
+
+    fn file_path(source_client: &str, target_client: &str) -> String {
+        format!(
+            "{}/{}-{}.json",
+            MIGRATIONS_DIRECTORY, source_client, target_client
+        )
+    }
+
+    /// The interrupted migration between the two accounts, or a new one.
+    pub fn load_or_new(source_client: &str, target_client: &str) -> Self {
+        fs::read_to_string(Self::file_path(source_client, target_client))
+            .ok()
+            .and_then(|v| serde_json::from_str::<Self>(&v).ok())
+            .filter(|v| v.finished_at.is_none())
+            .unwrap_or_else(|| Self::new(source_client, target_client))
+    }
+
+    pub fn save(&self) -> Result<(), ProcessError> {
+        fs::create_dir_all(MIGRATIONS_DIRECTORY)
+            .map_err(|err| ErrorKind::CannotSaveMigration.with_source(err))?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+        fs::write(
+            Self::file_path(&self.source_client, &self.target_client),
+            content,
+        )
+        .map_err(|err| ErrorKind::CannotSaveMigration.with_source(err))
+    }
+
+    pub fn finish(&mut self) {
+        self.finished_at = Some(Local::now().to_rfc3339());
+    }
+
+    pub fn migrated_files(&self) -> usize {
+        self.drives.values().map(|v| v.migrated.len()).sum()
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.drives.values().all(|v| v.is_verified())
+    }
+}
+
+impl DriveMigration {
+    pub fn is_verified(&self) -> bool {
+        self.failed.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl Display for DriveMigration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} files copied, {} in the target, {} failed, {} missing, {} duplicated",
+            self.migrated.len(),
+            self.source_files,
+            self.target_files,
+            self.failed.len(),
+            self.missing.len(),
+            self.duplicates.len()
+        )
+    }
+}
@@ -1,13 +1,45 @@
+mod backoff;
+mod bandwidth_limits;
 mod client;
 mod api_keys;
 mod file;
 mod file_metadata;
 mod dir;
+mod drive;
+mod job;
+mod migration;
 mod path;
+mod process_error;
+mod qr_login;
+mod rate_limiter;
+mod reconnect;
+mod scrub_report;
+mod session_info;
+mod settings;
+mod share_target;
+mod throttle;
+mod transfer_queue;
+mod transfer_state;
 
+pub use backoff::Backoff;
+pub use bandwidth_limits::{BandwidthLimits, BandwidthSchedule, RateLimit};
 pub use file::File;
 pub use api_keys::APIKeys;
 pub use client::Client;
 pub use file_metadata::FileMetadata;
 pub use dir::Dir;
+pub use drive::Drive;
+pub use job::Job;
+pub use migration::{DriveMigration, Migration};
 pub use path::Path;
+pub use process_error::ProcessError;
+pub use qr_login::QrLogin;
+pub use rate_limiter::RateLimiter;
+pub use reconnect::Reconnect;
+pub use scrub_report::ScrubReport;
+pub use session_info::SessionInfo;
+pub use settings::Settings;
+pub use share_target::ShareTarget;
+pub use throttle::Throttle;
+pub use transfer_queue::TransferQueue;
+pub use transfer_state::{TransferState, PART_SIZE};
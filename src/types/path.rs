@@ -1,4 +1,7 @@
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "String", into = "String")]
 pub struct Path {
     path: String,
     components: Vec<String>,
@@ -45,7 +48,7 @@ impl Path {
 
     pub fn pop(&mut self) {
         self.components.pop();
-        match self.components.len() > 0 {
+        match !self.components.is_empty() {
             true => self.path = format!("/{}/", self.components.join("/")),
             false => self.path = "/".to_string(),
         }
@@ -58,6 +61,12 @@ impl From<String> for Path {
     }
 }
 
+impl From<Path> for String {
+    fn from(path: Path) -> Self {
+        path.path
+    }
+}
+
 impl Default for Path {
     fn default() -> Self {
         Self {
@@ -0,0 +1,80 @@
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use grammers_mtsender::InvocationError;
+
+use crate::enums::{ErrorKind, ProcessResult, RetryReason};
+
+/// What went wrong, together with the operation and path it happened in and
+/// the underlying grammers or IO error.
+#[derive(Debug, Clone)]
+pub struct ProcessError {
+    pub kind: ErrorKind,
+    pub operation: Option<String>,
+    pub path: Option<String>,
+    source: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+impl ProcessError {
+    pub fn with_source(mut self, source: impl Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Display) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_operation(mut self, operation: impl Display) -> Self {
+        self.operation = Some(operation.to_string());
+        self
+    }
+
+    /// Whether the error came from a dropped connection rather than from the request itself.
+    pub fn is_network_error(&self) -> bool {
+        self.source
+            .as_deref()
+            .and_then(|v| v.downcast_ref::<InvocationError>())
+            .and_then(RetryReason::from_error)
+            .is_some_and(|v| matches!(v, RetryReason::Network))
+    }
+
+    pub fn into_process_result(self) -> ProcessResult {
+        ProcessResult::Error(self)
+    }
+}
+
+impl From<ErrorKind> for ProcessError {
+    fn from(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            operation: None,
+            path: None,
+            source: None,
+        }
+    }
+}
+
+impl Error for ProcessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|v| v.as_ref() as &(dyn Error + 'static))
+    }
+}
+
+impl Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(operation) = &self.operation {
+            write!(f, "{}: ", operation)?;
+        }
+        write!(f, "{}", self.kind)?;
+        if let Some(path) = &self.path {
+            write!(f, " ({})", path)?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, " Reason: {}", source)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+/// A login token shown as a QR code, to be scanned from an already logged in Telegram app.
+#[derive(Debug, Clone)]
+pub struct QrLogin {
+    pub url: String,
+    /// Unix time after which Telegram no longer accepts the token.
+    pub expires: i32,
+}
+
+impl QrLogin {
+    pub fn new(token: &[u8], expires: i32) -> Self {
+        Self {
+            url: format!("tg://login?token={}", URL_SAFE_NO_PAD.encode(token)),
+            expires,
+        }
+    }
+
+    pub fn expires_in(&self) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Duration::from_secs((self.expires as u64).saturating_sub(now))
+    }
+}
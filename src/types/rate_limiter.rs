@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+/// Spaces out transferred parts so that they don't exceed the given rate,
+/// shared by every transfer it is handed to.
+#[derive(Debug)]
+pub struct RateLimiter {
+    next_free: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits for the turn to transfer `bytes`. A rate of `0` means unlimited.
+    pub async fn acquire(&self, bytes: usize, bytes_per_second: u64) {
+        if bytes_per_second == 0 {
+            return;
+        }
+
+        let start = {
+            let mut next_free = self.next_free.lock().await;
+            let start = (*next_free).max(Instant::now());
+            *next_free = start + Duration::from_secs_f64(bytes as f64 / bytes_per_second as f64);
+            start
+        };
+
+        tokio::time::sleep_until(start.into()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u64 = 1_000_000;
+    const PART: usize = 100_000;
+
+    #[tokio::test]
+    async fn unlimited_rate_never_waits() {
+        let limiter = RateLimiter::new();
+        let started = Instant::now();
+        for _ in 0..100 {
+            limiter.acquire(PART, 0).await;
+        }
+        assert!(started.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn parts_are_spaced_by_the_rate() {
+        let limiter = RateLimiter::new();
+        let started = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire(PART, RATE).await;
+        }
+        // The first part goes at once, the other two wait 100 ms each.
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn rate_is_shared_between_transfers() {
+        let limiter = RateLimiter::new();
+        let started = Instant::now();
+        tokio::join!(
+            limiter.acquire(PART, RATE),
+            limiter.acquire(PART, RATE),
+            limiter.acquire(PART, RATE),
+        );
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,43 @@
+use std::{ops::ControlFlow, time::Duration};
+
+use grammers_mtsender::ReconnectionPolicy;
+
+use super::Backoff;
+
+/// Reconnects a dropped connection with the same delays as `Backoff`, so that
+/// requests retried after a network error have a live connection to go through.
+pub struct Reconnect;
+
+impl ReconnectionPolicy for Reconnect {
+    fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
+        let attempts = u32::try_from(attempts).unwrap_or(u32::MAX);
+        if Backoff::can_retry(attempts) {
+            ControlFlow::Continue(Backoff::delay(attempts))
+        } else {
+            ControlFlow::Break(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnects_with_backoff_delays() {
+        assert_eq!(
+            Reconnect.should_retry(0),
+            ControlFlow::Continue(Backoff::delay(0))
+        );
+        assert_eq!(
+            Reconnect.should_retry(2),
+            ControlFlow::Continue(Backoff::delay(2))
+        );
+    }
+
+    #[test]
+    fn stops_when_backoff_gives_up() {
+        assert_eq!(Reconnect.should_retry(100), ControlFlow::Break(()));
+        assert_eq!(Reconnect.should_retry(usize::MAX), ControlFlow::Break(()));
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fmt::Display, fs};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::enums::ErrorKind;
+
+use super::{Drive, ProcessError};
+
+pub const SCRUB_HISTORY_FILE: &str = "./scrub_history.json";
+const MAX_HISTORY_LENGTH: usize = 100;
+
+/// Outcome of re-downloading stored files and checking them against their hashes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScrubReport {
+    pub client_name: String,
+    /// Drive the files were checked in, empty for the main one.
+    pub drive: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub total_files: usize,
+    pub checked_files: usize,
+    /// Files uploaded before hashes were recorded, downloaded but not compared.
+    pub unhashed_files: usize,
+    pub mismatched: Vec<String>,
+    /// Path of the file and the reason it could not be downloaded.
+    pub unreadable: Vec<(String, String)>,
+}
+
+impl ScrubReport {
+    pub fn new(client_name: String, drive: String, total_files: usize) -> Self {
+        Self {
+            client_name,
+            drive,
+            started_at: Local::now().to_rfc3339(),
+            finished_at: String::new(),
+            total_files,
+            checked_files: 0,
+            unhashed_files: 0,
+            mismatched: Vec::new(),
+            unreadable: Vec::new(),
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.finished_at = Local::now().to_rfc3339();
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.mismatched.is_empty() && self.unreadable.is_empty()
+    }
+
+    pub fn load_history() -> Vec<Self> {
+        fs::read_to_string(SCRUB_HISTORY_FILE)
+            .ok()
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn append_to_history(&self) -> Result<Vec<Self>, ProcessError> {
+        let mut history = Self::load_history();
+        history.push(self.clone());
+        if history.len() > MAX_HISTORY_LENGTH {
+            history.drain(..history.len() - MAX_HISTORY_LENGTH);
+        }
+
+        let content =
+            serde_json::to_string(&history).map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+        fs::write(SCRUB_HISTORY_FILE, content).map_err(|err| ErrorKind::CannotSaveScrubHistory.with_source(err))?;
+
+        Ok(history)
+    }
+}
+
+impl Display for ScrubReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} ({}): {} of {} files checked, {} without hash, {} mismatched, {} unreadable",
+            self.started_at,
+            self.client_name,
+            Drive::label(&self.drive),
+            self.checked_files,
+            self.total_files,
+            self.unhashed_files,
+            self.mismatched.len(),
+            self.unreadable.len()
+        )
+    }
+}
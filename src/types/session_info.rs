@@ -0,0 +1,49 @@
+use std::{collections::BTreeMap, fs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::enums::{AccountKind, ErrorKind};
+
+use super::ProcessError;
+
+/// What has to be known about a saved session besides its authorization,
+/// kept in `./sessions/<session id>.json` next to the session file.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SessionInfo {
+    pub kind: AccountKind,
+    /// Packed chat ids of the drives by drive name, the main drive has an empty name.
+    pub drives: BTreeMap<String, String>,
+}
+
+impl SessionInfo {
+    pub fn new(kind: AccountKind) -> Self {
+        Self {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    pub fn path(session_id: &str) -> String {
+        format!("./sessions/{}.json", session_id)
+    }
+
+    /// Sessions saved before this file existed are user accounts.
+    pub fn load(session_id: &str) -> Self {
+        fs::read_to_string(Self::path(session_id))
+            .ok()
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, session_id: &str) -> Result<(), ProcessError> {
+        let content = serde_json::to_string(self)
+            .map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+        fs::write(Self::path(session_id), content)
+            .map_err(|err| ErrorKind::CannotSaveSessionInFile.with_source(err))
+    }
+
+    pub fn remove(session_id: &str) {
+        let _ = fs::remove_file(Self::path(session_id));
+    }
+}
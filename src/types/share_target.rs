@@ -0,0 +1,14 @@
+use grammers_session::PackedChat;
+
+/// A chat or contact a file can be forwarded to.
+#[derive(Debug, Clone)]
+pub struct ShareTarget {
+    pub name: String,
+    pub chat: PackedChat,
+}
+
+impl ShareTarget {
+    pub fn new(name: String, chat: PackedChat) -> Self {
+        Self { name, chat }
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, RwLock,
+};
+
+use crate::enums::TransferDirection;
+
+use super::{BandwidthLimits, RateLimiter, Settings};
+
+/// Applies both the global and the per job rate limit to one transfer.
+#[derive(Clone)]
+pub struct Throttle {
+    direction: TransferDirection,
+    bandwidth_limits: Arc<RwLock<BandwidthLimits>>,
+    global_limiter: Arc<RateLimiter>,
+    job_limit: Arc<AtomicU64>,
+    job_limiter: Arc<RateLimiter>,
+}
+
+impl Throttle {
+    pub fn new(
+        direction: TransferDirection,
+        bandwidth_limits: Arc<RwLock<BandwidthLimits>>,
+        global_limiter: Arc<RateLimiter>,
+        job_limit: Arc<AtomicU64>,
+    ) -> Self {
+        Self {
+            direction,
+            bandwidth_limits,
+            global_limiter,
+            job_limit,
+            job_limiter: Arc::new(RateLimiter::new()),
+        }
+    }
+
+    /// A throttle for transfers outside of the queue, limited only by the saved global limits.
+    pub fn with_saved_limits(direction: TransferDirection) -> Self {
+        Self::new(
+            direction,
+            Arc::new(RwLock::new(Settings::load().bandwidth_limits)),
+            Arc::new(RateLimiter::new()),
+            Arc::new(AtomicU64::new(0)),
+        )
+    }
+
+    pub async fn wait(&self, bytes: usize) {
+        let global_limit = self
+            .bandwidth_limits
+            .read()
+            .map(|v| v.current().get(self.direction))
+            .unwrap_or_default();
+
+        self.job_limiter
+            .acquire(bytes, self.job_limit.load(Ordering::Relaxed))
+            .await;
+        self.global_limiter.acquire(bytes, global_limit).await;
+    }
+}
@@ -0,0 +1,336 @@
+use std::sync::{atomic::AtomicU64, Arc, RwLock};
+
+use crate::enums::{JobKind, JobStatus, TransferDirection};
+
+use super::{BandwidthLimits, Job, ProcessError, RateLimiter, Throttle, TransferState};
+
+pub const DEFAULT_PARALLELISM: usize = 2;
+/// Reconnects a single job may wait for before its failure is final.
+const MAX_RECONNECTS: u32 = 3;
+
+#[derive(Debug)]
+pub struct TransferQueue {
+    pub jobs: Vec<Job>,
+    pub parallelism: usize,
+    pub bandwidth_limits: Arc<RwLock<BandwidthLimits>>,
+    upload_limiter: Arc<RateLimiter>,
+    download_limiter: Arc<RateLimiter>,
+    next_job_id: usize,
+}
+
+impl TransferQueue {
+    pub fn new() -> Self {
+        Self {
+            jobs: Vec::new(),
+            parallelism: DEFAULT_PARALLELISM,
+            bandwidth_limits: Arc::new(RwLock::new(BandwidthLimits::default())),
+            upload_limiter: Arc::new(RateLimiter::new()),
+            download_limiter: Arc::new(RateLimiter::new()),
+            next_job_id: 0,
+        }
+    }
+
+    pub fn push(&mut self, client_name: String, drive: String, kind: JobKind) -> usize {
+        let id = self.next_job_id;
+        self.next_job_id += 1;
+        self.jobs.push(Job::new(id, client_name, drive, kind));
+        id
+    }
+
+    pub fn get_mut(&mut self, job_id: usize) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == job_id)
+    }
+
+    pub fn throttle(&self, job_id: usize) -> Option<Throttle> {
+        let job = self.jobs.iter().find(|job| job.id == job_id)?;
+
+        Some(self.throttle_with_job_limit(job.kind.direction(), job.rate_limit.clone()))
+    }
+
+    /// Throttle for transfers that are not jobs of the queue but share its global limits.
+    pub fn unqueued_throttle(&self, direction: TransferDirection) -> Throttle {
+        self.throttle_with_job_limit(direction, Arc::new(AtomicU64::new(0)))
+    }
+
+    fn throttle_with_job_limit(
+        &self,
+        direction: TransferDirection,
+        job_limit: Arc<AtomicU64>,
+    ) -> Throttle {
+        let global_limiter = match direction {
+            TransferDirection::Upload => &self.upload_limiter,
+            TransferDirection::Download => &self.download_limiter,
+        };
+
+        Throttle::new(
+            direction,
+            self.bandwidth_limits.clone(),
+            global_limiter.clone(),
+            job_limit,
+        )
+    }
+
+    pub fn contains(&self, client_name: &str, drive: &str, kind: &JobKind) -> bool {
+        let transfer_key = kind.transfer_key(client_name, drive);
+        self.jobs
+            .iter()
+            .any(|job| job.transfer_key() == transfer_key)
+    }
+
+    /// Removes the job from the queue and forgets its saved progress.
+    pub fn remove(&mut self, job_id: usize) {
+        if let Some(index) = self.jobs.iter().position(|job| job.id == job_id) {
+            let mut job = self.jobs.remove(index);
+            job.pause();
+            TransferState::load_or_new(&job.client_name, &job.drive, &job.kind).remove();
+        }
+    }
+
+    /// Stops and removes every job of the client, e.g. once its session is deleted.
+    pub fn remove_client_jobs(&mut self, client_name: &str) {
+        let job_ids = self
+            .jobs
+            .iter()
+            .filter(|job| job.client_name == client_name)
+            .map(|job| job.id)
+            .collect::<Vec<_>>();
+        for job_id in job_ids {
+            self.remove(job_id);
+        }
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status.is_active())
+            .count()
+    }
+
+    /// Ids of queued jobs that fit into the free parallelism slots, in queue order.
+    pub fn jobs_to_start(&self) -> Vec<usize> {
+        let free_slots = self.parallelism.saturating_sub(self.running_count());
+        self.jobs
+            .iter()
+            .filter(|job| matches!(job.status, JobStatus::Queued))
+            .take(free_slots)
+            .map(|job| job.id)
+            .collect()
+    }
+
+    pub fn move_up(&mut self, job_id: usize) {
+        if let Some(index) = self.jobs.iter().position(|job| job.id == job_id) {
+            if index > 0 {
+                self.jobs.swap(index, index - 1);
+            }
+        }
+    }
+
+    pub fn move_down(&mut self, job_id: usize) {
+        if let Some(index) = self.jobs.iter().position(|job| job.id == job_id) {
+            if index + 1 < self.jobs.len() {
+                self.jobs.swap(index, index + 1);
+            }
+        }
+    }
+
+    pub fn pause(&mut self, job_id: usize) {
+        if let Some(job) = self.get_mut(job_id) {
+            if matches!(job.status, JobStatus::Queued) || job.status.is_active() {
+                job.pause();
+            }
+        }
+    }
+
+    /// Puts a paused or failed job back into the queue.
+    pub fn requeue(&mut self, job_id: usize) {
+        if let Some(job) = self.get_mut(job_id) {
+            if matches!(job.status, JobStatus::Paused | JobStatus::Failed(_)) {
+                job.status = JobStatus::Queued;
+            }
+        }
+    }
+
+    /// Fails the job and, if its connection dropped, marks it to resume from the
+    /// saved progress once the client reconnects. Returns `true` if the reconnect
+    /// still has to be started, that is no other job of the client waits for it.
+    pub fn fail(&mut self, job_id: usize, error: ProcessError) -> bool {
+        let is_network_error = error.is_network_error();
+        let job = match self.get_mut(job_id) {
+            Some(v) if v.status.is_active() => v,
+            _ => return false,
+        };
+        job.set_finished(JobStatus::Failed(error));
+        if !is_network_error || job.reconnects >= MAX_RECONNECTS {
+            return false;
+        }
+        job.awaiting_reconnect = true;
+        job.reconnects += 1;
+
+        let client_name = job.client_name.clone();
+        !self
+            .jobs
+            .iter()
+            .any(|job| job.id != job_id && job.awaiting_reconnect && job.client_name == client_name)
+    }
+
+    /// Puts the jobs that failed on a dropped connection of the client back into the queue.
+    pub fn resume_after_reconnect(&mut self, client_name: &str) {
+        for job in &mut self.jobs {
+            if job.awaiting_reconnect && job.client_name == client_name {
+                job.awaiting_reconnect = false;
+                if matches!(job.status, JobStatus::Failed(_)) {
+                    job.status = JobStatus::Queued;
+                }
+            }
+        }
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|job| !job.status.is_finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grammers_mtsender::InvocationError;
+
+    use crate::{
+        enums::ErrorKind,
+        types::{File, FileMetadata},
+    };
+
+    use super::*;
+
+    fn queue(client_names: &[&str]) -> TransferQueue {
+        let mut queue = TransferQueue::new();
+        for (message_id, client_name) in client_names.iter().enumerate() {
+            let file = File::new(
+                FileMetadata::new(format!("/{}.txt", message_id), None),
+                message_id as i32,
+            );
+            queue.push(
+                client_name.to_string(),
+                String::new(),
+                JobKind::Download(file),
+            );
+        }
+        queue
+    }
+
+    fn ids(queue: &TransferQueue) -> Vec<usize> {
+        queue.jobs.iter().map(|job| job.id).collect()
+    }
+
+    fn start(queue: &mut TransferQueue, job_id: usize) {
+        queue.get_mut(job_id).unwrap().status = JobStatus::Running;
+    }
+
+    fn network_error() -> ProcessError {
+        ErrorKind::CannotDownloadMedia.with_source(InvocationError::Dropped)
+    }
+
+    #[test]
+    fn jobs_to_start_fill_free_slots_in_order() {
+        let mut queue = queue(&["a", "a", "a", "a"]);
+        queue.parallelism = 2;
+        assert_eq!(queue.jobs_to_start(), [0, 1]);
+
+        start(&mut queue, 0);
+        assert_eq!(queue.jobs_to_start(), [1]);
+
+        start(&mut queue, 1);
+        assert!(queue.jobs_to_start().is_empty());
+    }
+
+    #[test]
+    fn paused_jobs_are_not_started() {
+        let mut queue = queue(&["a", "a", "a"]);
+        queue.pause(0);
+        assert_eq!(queue.jobs_to_start(), [1, 2]);
+    }
+
+    #[test]
+    fn jobs_move_up_and_down() {
+        let mut queue = queue(&["a", "a", "a"]);
+        queue.move_up(2);
+        assert_eq!(ids(&queue), [0, 2, 1]);
+        queue.move_down(0);
+        assert_eq!(ids(&queue), [2, 0, 1]);
+    }
+
+    #[test]
+    fn jobs_stay_at_the_ends() {
+        let mut queue = queue(&["a", "a"]);
+        queue.move_up(0);
+        queue.move_down(1);
+        queue.move_up(7);
+        assert_eq!(ids(&queue), [0, 1]);
+    }
+
+    #[test]
+    fn only_jobs_of_the_client_are_removed() {
+        let mut queue = queue(&["a", "b", "a"]);
+        start(&mut queue, 0);
+        queue.remove_client_jobs("a");
+        assert_eq!(ids(&queue), [1]);
+    }
+
+    #[test]
+    fn permanent_failure_is_final() {
+        let mut queue = queue(&["a"]);
+        start(&mut queue, 0);
+
+        assert!(!queue.fail(0, ErrorKind::HashMismatch.into()));
+        assert!(!queue.jobs[0].awaiting_reconnect);
+        queue.resume_after_reconnect("a");
+        assert!(matches!(queue.jobs[0].status, JobStatus::Failed(_)));
+    }
+
+    #[test]
+    fn only_active_jobs_fail() {
+        let mut queue = queue(&["a"]);
+        assert!(!queue.fail(0, network_error()));
+        assert!(matches!(queue.jobs[0].status, JobStatus::Queued));
+    }
+
+    #[test]
+    fn one_reconnect_per_client() {
+        let mut queue = queue(&["a", "a", "b"]);
+        for job_id in 0..3 {
+            start(&mut queue, job_id);
+        }
+
+        assert!(queue.fail(0, network_error()));
+        assert!(!queue.fail(1, network_error()));
+        assert!(queue.fail(2, network_error()));
+    }
+
+    #[test]
+    fn reconnect_resumes_jobs_of_the_client() {
+        let mut queue = queue(&["a", "a", "b"]);
+        for job_id in 0..3 {
+            start(&mut queue, job_id);
+            queue.fail(job_id, network_error());
+        }
+
+        queue.resume_after_reconnect("a");
+        assert!(matches!(queue.jobs[0].status, JobStatus::Queued));
+        assert!(matches!(queue.jobs[1].status, JobStatus::Queued));
+        assert!(matches!(queue.jobs[2].status, JobStatus::Failed(_)));
+        assert!(queue.jobs[2].awaiting_reconnect);
+    }
+
+    #[test]
+    fn reconnects_are_limited() {
+        let mut queue = queue(&["a"]);
+        for _ in 0..MAX_RECONNECTS {
+            start(&mut queue, 0);
+            assert!(queue.fail(0, network_error()));
+            queue.resume_after_reconnect("a");
+        }
+
+        start(&mut queue, 0);
+        assert!(!queue.fail(0, network_error()));
+        assert!(matches!(queue.jobs[0].status, JobStatus::Failed(_)));
+    }
+}
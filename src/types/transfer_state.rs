@@ -0,0 +1,98 @@
+use std::{fs, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::enums::{ErrorKind, JobKind};
+
+use super::ProcessError;
+
+pub const TRANSFERS_DIRECTORY: &str = "./transfers";
+pub const PART_SIZE: usize = 512 * 1024;
+
+/// Progress of an upload or download, persisted after every confirmed part
+/// so that an interrupted transfer can continue where it stopped.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransferState {
+    pub client_name: String,
+    /// Drive of the client the transfer belongs to, empty for the main one.
+    pub drive: String,
+    pub kind: JobKind,
+    /// Random id that Telegram groups the uploaded parts by. `None` for downloads.
+    pub upload_file_id: Option<i64>,
+    pub file_size: u64,
+    /// Modification time of the uploaded file, a changed file is uploaded from the start.
+    pub modified: Option<SystemTime>,
+    pub confirmed_parts: i32,
+}
+
+impl TransferState {
+    fn new(client_name: &str, drive: &str, kind: &JobKind) -> Self {
+        Self {
+            client_name: client_name.to_string(),
+            drive: drive.to_string(),
+            kind: kind.clone(),
+            upload_file_id: None,
+            file_size: 0,
+            modified: None,
+            confirmed_parts: 0,
+        }
+    }
+
+    /// Key of the transfer, unique across clients, drives and files.
+    pub fn key(&self) -> String {
+        self.kind.transfer_key(&self.client_name, &self.drive)
+    }
+
+    fn file_path(&self) -> String {
+        format!("{}/{}.json", TRANSFERS_DIRECTORY, self.key())
+    }
+
+    pub fn load_or_new(client_name: &str, drive: &str, kind: &JobKind) -> Self {
+        let state = Self::new(client_name, drive, kind);
+
+        fs::read_to_string(state.file_path())
+            .ok()
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or(state)
+    }
+
+    pub fn load_all() -> Vec<Self> {
+        let transfer_files = match fs::read_dir(TRANSFERS_DIRECTORY) {
+            Ok(v) => v,
+            Err(_) => return Vec::new(),
+        };
+
+        transfer_files
+            .filter_map(|v| v.ok())
+            .filter_map(|v| fs::read_to_string(v.path()).ok())
+            .filter_map(|v| serde_json::from_str(&v).ok())
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<(), ProcessError> {
+        let content =
+            serde_json::to_string(self).map_err(|err| ErrorKind::CannotSerializeToString.with_source(err))?;
+        fs::write(self.file_path(), content).map_err(|err| ErrorKind::CannotSaveTransferState.with_source(err))
+    }
+
+    pub fn remove(&self) {
+        let _ = fs::remove_file(self.file_path());
+    }
+
+    /// Starts the transfer over, e.g. when the local file changed since the last attempt.
+    pub fn reset(&mut self, file_size: u64) {
+        self.file_size = file_size;
+        self.confirmed_parts = 0;
+        if let JobKind::Upload { .. } = self.kind {
+            self.upload_file_id = Some(rand::random());
+        }
+    }
+
+    pub fn total_parts(&self) -> i32 {
+        (self.file_size.div_ceil(PART_SIZE as u64) as i32).max(1)
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.confirmed_parts as f32 / self.total_parts() as f32
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use eframe::egui::{
+    self, Button, Color32, ComboBox, Context, Grid, Layout, RichText, TextEdit, Ui,
+};
+
+use crate::{
+    enums::{NewProcess, SessionStatus},
+    types::{Client, Drive, Migration},
+    ui::{tab::Tab, window::Window},
+};
+
+pub struct Accounts {
+    /// Ids of the saved sessions, read again when the tab opens or a session is added or removed.
+    session_ids: Vec<String>,
+    pending_removal: Option<String>,
+    /// Chats typed to bind accounts with a missing cloud chat to, by session id.
+    chat_addresses: BTreeMap<String, String>,
+    migration_source: String,
+    migration_target: String,
+    pub migration_report: Option<Migration>,
+}
+
+impl Accounts {
+    pub fn new() -> Self {
+        Self {
+            session_ids: Client::saved_session_ids(),
+            pending_removal: None,
+            chat_addresses: BTreeMap::new(),
+            migration_source: String::new(),
+            migration_target: String::new(),
+            migration_report: None,
+        }
+    }
+
+    pub fn refresh_sessions(&mut self) {
+        self.session_ids = Client::saved_session_ids();
+    }
+
+    fn remove_session(window: &mut Window, session_id: String) {
+        if window.settings.confirm_delete {
+            window.accounts_tab.pending_removal = Some(session_id);
+        } else {
+            NewProcess::RemoveSession(session_id).start(window);
+        }
+    }
+
+    fn confirm_removal_ui(window: &mut Window, ctx: &Context) {
+        let session_id = match &window.accounts_tab.pending_removal {
+            Some(v) => v.clone(),
+            None => return,
+        };
+
+        egui::Window::new("Delete session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Delete the session of {}? Its transfers are cancelled.",
+                    window.client_label(&session_id)
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        window.accounts_tab.pending_removal = None;
+                        NewProcess::RemoveSession(session_id.clone()).start(window);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        window.accounts_tab.pending_removal = None;
+                    }
+                });
+            });
+    }
+
+    fn migration_ui(window: &mut Window, ui: &mut Ui) {
+        ui.heading("Migrate cloud");
+        ui.label(
+            "Copy every drive and file of one account to another, keeping the paths. \
+             An interrupted migration continues where it stopped.",
+        );
+
+        let session_ids = window.clients.keys().cloned().collect::<Vec<_>>();
+        ui.horizontal(|ui| {
+            ui.label("From ");
+            let source = window.accounts_tab.migration_source.clone();
+            ComboBox::from_id_source("migration-source")
+                .selected_text(window.client_label(&source))
+                .show_ui(ui, |ui| {
+                    for session_id in &session_ids {
+                        let label = window.client_label(session_id);
+                        ui.selectable_value(
+                            &mut window.accounts_tab.migration_source,
+                            session_id.clone(),
+                            label,
+                        );
+                    }
+                });
+            ui.label(" to ");
+            let target = window.accounts_tab.migration_target.clone();
+            ComboBox::from_id_source("migration-target")
+                .selected_text(window.client_label(&target))
+                .show_ui(ui, |ui| {
+                    for session_id in &session_ids {
+                        let label = window.client_label(session_id);
+                        ui.selectable_value(
+                            &mut window.accounts_tab.migration_target,
+                            session_id.clone(),
+                            label,
+                        );
+                    }
+                });
+
+            let is_ready = !source.is_empty() && !target.is_empty() && source != target;
+            if ui
+                .add_enabled(is_ready, Button::new("Migrate"))
+                .on_hover_text("Files are forwarded between the accounts, nothing is downloaded")
+                .clicked()
+            {
+                NewProcess::MigrateCloud(source, target).start(window);
+            }
+        });
+
+        let migration = match &window.accounts_tab.migration_report {
+            Some(v) => v.clone(),
+            None => return,
+        };
+        ui.separator();
+        if migration.is_verified() {
+            ui.label(RichText::new("Migration verified").color(Color32::GREEN));
+        } else {
+            ui.label(RichText::new("Migration is incomplete").color(Color32::YELLOW));
+        }
+        ui.label(format!(
+            "From {} to {}, started {}",
+            window.client_label(&migration.source_client),
+            window.client_label(&migration.target_client),
+            migration.started_at
+        ));
+        for (name, drive) in &migration.drives {
+            ui.label(format!("{}: {}", Drive::label(name), drive));
+            for reason in drive.failed.values() {
+                ui.label(RichText::new(reason).color(Color32::RED));
+            }
+            for path in &drive.missing {
+                ui.label(RichText::new(format!("Missing: {}", path)).color(Color32::YELLOW));
+            }
+            for path in &drive.duplicates {
+                ui.label(format!("Duplicated: {}", path));
+            }
+        }
+    }
+
+    pub fn ui(window: &mut Window, ctx: &Context) {
+        window.header(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let sessions = window
+                .accounts_tab
+                .session_ids
+                .clone()
+                .into_iter()
+                .map(|session_id| {
+                    let status = window.session_statuses.get(&session_id).cloned();
+                    let name = match (window.clients.get(&session_id), &status) {
+                        (Some(client), _) => client.display_name(),
+                        (None, Some(SessionStatus::CloudChatMissing(client))) => {
+                            client.display_name()
+                        }
+                        (None, _) => format!("User {}", session_id),
+                    };
+                    (session_id, name, status)
+                })
+                .collect::<Vec<_>>();
+
+            if sessions.is_empty() {
+                ui.label("There are no saved sessions yet. Add one in the \"New session\" tab.");
+                return;
+            }
+
+            let mut is_changed = false;
+
+            Grid::new("Accounts")
+                .num_columns(6)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Account");
+                    ui.strong("Label");
+                    ui.strong("Proxy");
+                    ui.strong("Status");
+                    ui.strong("Default");
+                    ui.end_row();
+
+                    for (session_id, name, status) in sessions {
+                        ui.label(name);
+
+                        let mut label = window
+                            .settings
+                            .account_labels
+                            .get(&session_id)
+                            .cloned()
+                            .unwrap_or_default();
+                        if ui
+                            .add(TextEdit::singleline(&mut label).hint_text("Display label"))
+                            .changed()
+                        {
+                            if label.is_empty() {
+                                window.settings.account_labels.remove(&session_id);
+                            } else {
+                                window
+                                    .settings
+                                    .account_labels
+                                    .insert(session_id.clone(), label);
+                            }
+                            is_changed = true;
+                        }
+
+                        let mut proxy = window
+                            .settings
+                            .account_proxies
+                            .get(&session_id)
+                            .cloned()
+                            .unwrap_or_default();
+                        if ui
+                            .add(TextEdit::singleline(&mut proxy).hint_text("Global proxy"))
+                            .on_hover_text(
+                                "SOCKS5 proxy of this account, used from the next connection. \
+                                MTProxy is not supported by grammers 0.6, the Telegram library.",
+                            )
+                            .changed()
+                        {
+                            if proxy.is_empty() {
+                                window.settings.account_proxies.remove(&session_id);
+                            } else {
+                                window
+                                    .settings
+                                    .account_proxies
+                                    .insert(session_id.clone(), proxy);
+                            }
+                            is_changed = true;
+                        }
+
+                        match &status {
+                            Some(SessionStatus::Connected) => {
+                                ui.label(RichText::new("Connected").color(Color32::GREEN));
+                            }
+                            Some(SessionStatus::CloudChatMissing(_)) => {
+                                ui.label(
+                                    RichText::new("Cloud chat missing").color(Color32::YELLOW),
+                                );
+                            }
+                            Some(SessionStatus::Revoked) => {
+                                ui.label(RichText::new("Revoked").color(Color32::RED))
+                                    .on_hover_text("The session was ended on Telegram or expired");
+                            }
+                            Some(SessionStatus::Error(err)) => {
+                                ui.label(RichText::new("Error").color(Color32::RED))
+                                    .on_hover_text(err.to_string());
+                            }
+                            None => {
+                                ui.label(RichText::new("Not connected").color(Color32::GRAY));
+                            }
+                        }
+
+                        let is_default =
+                            window.settings.default_account.as_ref() == Some(&session_id);
+                        if ui.radio(is_default, "").clicked() {
+                            window.settings.default_account =
+                                (!is_default).then(|| session_id.clone());
+                            is_changed = true;
+                        }
+
+                        ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+                            if ui
+                                .button("Delete session")
+                                .on_hover_text(
+                                    "Remove the local session file without logging out on Telegram",
+                                )
+                                .clicked()
+                            {
+                                Self::remove_session(window, session_id.clone());
+                            }
+                            match status {
+                                Some(SessionStatus::Revoked) => {
+                                    if ui.button("Log in again").clicked() {
+                                        window.tab = Tab::NewSession;
+                                    }
+                                }
+                                Some(SessionStatus::CloudChatMissing(client))
+                                    if client.is_bot() =>
+                                {
+                                    if ui
+                                        .button("Retry")
+                                        .on_hover_text(
+                                            "The bot lost access to its storage chat, make it an admin there again",
+                                        )
+                                        .clicked()
+                                    {
+                                        NewProcess::ReconnectSession(session_id.clone())
+                                            .start(window);
+                                    }
+                                }
+                                Some(SessionStatus::CloudChatMissing(_)) => {
+                                    let address = window
+                                        .accounts_tab
+                                        .chat_addresses
+                                        .get(&session_id)
+                                        .map(|v| v.trim().to_string())
+                                        .unwrap_or_default();
+                                    if ui
+                                        .add_enabled(!address.is_empty(), Button::new("Bind chat"))
+                                        .on_hover_text(
+                                            "Use an existing group or channel as the cloud chat",
+                                        )
+                                        .clicked()
+                                    {
+                                        window.accounts_tab.chat_addresses.remove(&session_id);
+                                        NewProcess::RebindDrive(
+                                            session_id.clone(),
+                                            String::new(),
+                                            address,
+                                        )
+                                        .start(window);
+                                    }
+                                    ui.add(
+                                        TextEdit::singleline(
+                                            window
+                                                .accounts_tab
+                                                .chat_addresses
+                                                .entry(session_id.clone())
+                                                .or_default(),
+                                        )
+                                        .hint_text("@username or chat id")
+                                        .desired_width(140.0),
+                                    );
+                                    if ui
+                                        .button("Create cloud chat")
+                                        .on_hover_text(
+                                            "Create a new TuchaCloud chat for this account",
+                                        )
+                                        .clicked()
+                                    {
+                                        NewProcess::RepairCloudChat(session_id.clone())
+                                            .start(window);
+                                    }
+                                }
+                                Some(SessionStatus::Error(_)) | None => {
+                                    if ui.button("Retry").clicked() {
+                                        NewProcess::ReconnectSession(session_id.clone())
+                                            .start(window);
+                                    }
+                                }
+                                Some(SessionStatus::Connected) => {}
+                            }
+                            if ui
+                                .add_enabled(
+                                    window.clients.contains_key(&session_id),
+                                    Button::new("Log out"),
+                                )
+                                .on_hover_text(
+                                    "Revoke this session on Telegram and remove it locally",
+                                )
+                                .clicked()
+                            {
+                                NewProcess::LogOut(session_id.clone()).start(window);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+
+            if is_changed {
+                window.apply_settings();
+            }
+
+            ui.separator();
+            Self::migration_ui(window, ui);
+        });
+        Self::confirm_removal_ui(window, ctx);
+        window.footer(ctx);
+    }
+}
@@ -1,8 +1,14 @@
 use std::collections::BTreeMap;
 
-use eframe::egui::{self, Context, Grid, Label, Layout, TextEdit};
+use eframe::egui::{
+    self, Button, Checkbox, ComboBox, Context, Grid, Label, Layout, ScrollArea, TextEdit,
+};
 
-use crate::{enums::NewProcess, types::{Dir, Path}, ui::window::Window};
+use crate::{
+    enums::NewProcess,
+    types::{Dir, Drive, File, Path, ShareTarget},
+    ui::window::Window,
+};
 
 #[derive(Clone)]
 pub struct Cloud {
@@ -10,6 +16,17 @@ pub struct Cloud {
     pub current_path: Path,
     is_creating_folder: bool,
     new_dir_name: String,
+    pending_delete: Option<Vec<File>>,
+    pending_share: Option<File>,
+    /// Files to copy and the directory their paths are kept relative to.
+    pending_copy: Option<(Vec<File>, Path)>,
+    copy_destination: String,
+    copy_target_client: String,
+    copy_target_drive: String,
+    is_move: bool,
+    /// Chats offered when sharing, by client.
+    pub share_targets: BTreeMap<String, Vec<ShareTarget>>,
+    share_filter: String,
 }
 
 impl Cloud {
@@ -19,16 +36,248 @@ impl Cloud {
             current_path: Path::default(),
             is_creating_folder: false,
             new_dir_name: String::new(),
+            pending_delete: None,
+            pending_share: None,
+            pending_copy: None,
+            copy_destination: String::new(),
+            copy_target_client: String::new(),
+            copy_target_drive: String::new(),
+            is_move: false,
+            share_targets: BTreeMap::new(),
+            share_filter: String::new(),
         }
     }
 
+    fn delete(window: &mut Window, files: Vec<File>) {
+        if window.settings.confirm_delete {
+            window.cloud_tab.pending_delete = Some(files);
+        } else {
+            NewProcess::DeleteFiles(files).start(window);
+        }
+    }
+
+    fn confirm_delete_ui(window: &mut Window, ctx: &Context) {
+        let files_count = match &window.cloud_tab.pending_delete {
+            Some(v) => v.len(),
+            None => return,
+        };
+
+        egui::Window::new("Delete files")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Delete {} file(s) from the cloud?", files_count));
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        if let Some(files) = window.cloud_tab.pending_delete.take() {
+                            NewProcess::DeleteFiles(files).start(window);
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        window.cloud_tab.pending_delete = None;
+                    }
+                });
+            });
+    }
+
+    fn copy(window: &mut Window, files: Vec<File>) {
+        let source = window.cloud_tab.current_path.clone();
+        window.cloud_tab.copy_destination = source.path();
+        window.cloud_tab.copy_target_client = window.current_client.clone();
+        window.cloud_tab.copy_target_drive = window.current_drive();
+        window.cloud_tab.is_move = false;
+        window.cloud_tab.pending_copy = Some((files, source));
+    }
+
+    fn copy_ui(window: &mut Window, ctx: &Context) {
+        let files_count = match &window.cloud_tab.pending_copy {
+            Some((files, _)) => files.len(),
+            None => return,
+        };
+
+        egui::Window::new("Copy files")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Copy {} file(s) without downloading or uploading them again.",
+                    files_count
+                ));
+                Grid::new("Copy files").num_columns(2).show(ui, |ui| {
+                    ui.label("Account: ");
+                    let target_client = window.cloud_tab.copy_target_client.clone();
+                    ComboBox::from_id_source("copy-target-client")
+                        .selected_text(window.client_label(&target_client))
+                        .show_ui(ui, |ui| {
+                            for session_id in window.clients.keys() {
+                                let label = window.client_label(session_id);
+                                ui.selectable_value(
+                                    &mut window.cloud_tab.copy_target_client,
+                                    session_id.clone(),
+                                    label,
+                                );
+                            }
+                        });
+                    if window.cloud_tab.copy_target_client != target_client {
+                        window.cloud_tab.copy_target_drive.clear();
+                    }
+                    ui.end_row();
+
+                    ui.label("Drive: ");
+                    let drives = window
+                        .clients
+                        .get(&window.cloud_tab.copy_target_client)
+                        .map(|v| v.drives.clone())
+                        .unwrap_or_default();
+                    ComboBox::from_id_source("copy-target-drive")
+                        .selected_text(Drive::label(&window.cloud_tab.copy_target_drive))
+                        .show_ui(ui, |ui| {
+                            for drive in &drives {
+                                ui.selectable_value(
+                                    &mut window.cloud_tab.copy_target_drive,
+                                    drive.name.clone(),
+                                    Drive::label(&drive.name),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Directory: ");
+                    ui.add(
+                        TextEdit::singleline(&mut window.cloud_tab.copy_destination)
+                            .hint_text("/backup/"),
+                    );
+                    ui.end_row();
+                });
+
+                // Another drive or account is reached by forwarding, which can also move.
+                let is_transfer = window.cloud_tab.copy_target_client != window.current_client
+                    || window.cloud_tab.copy_target_drive != window.current_drive();
+                ui.add_enabled(
+                    is_transfer,
+                    Checkbox::new(&mut window.cloud_tab.is_move, "Delete the source files"),
+                );
+
+                ui.horizontal(|ui| {
+                    if ui.button("Copy").clicked() {
+                        if let Some((files, source)) = window.cloud_tab.pending_copy.take() {
+                            let destination =
+                                match window.cloud_tab.copy_destination.trim_matches('/') {
+                                    "" => Path::default(),
+                                    v => Path::new(&format!("/{}/", v)),
+                                };
+                            if is_transfer {
+                                NewProcess::TransferFiles {
+                                    files,
+                                    source,
+                                    destination,
+                                    target_client: window.cloud_tab.copy_target_client.clone(),
+                                    target_drive: window.cloud_tab.copy_target_drive.clone(),
+                                    is_move: window.cloud_tab.is_move,
+                                }
+                                .start(window);
+                            } else {
+                                NewProcess::CopyFiles(files, source, destination).start(window);
+                            }
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        window.cloud_tab.pending_copy = None;
+                    }
+                });
+            });
+    }
+
+    fn share(window: &mut Window, file: File) {
+        window.cloud_tab.pending_share = Some(file);
+        window.cloud_tab.share_filter.clear();
+        let is_bot = window
+            .clients
+            .get(&window.current_client)
+            .is_some_and(|v| v.is_bot());
+        // Bots cannot list their dialogs, so they only get the link.
+        if !is_bot && !window.cloud_tab.share_targets.contains_key(&window.current_client) {
+            NewProcess::GetShareTargets.start(window);
+        }
+    }
+
+    fn share_ui(window: &mut Window, ctx: &Context) {
+        let file = match &window.cloud_tab.pending_share {
+            Some(v) => v.clone(),
+            None => return,
+        };
+        let client = window.clients.get(&window.current_client);
+        let link = client.and_then(|v| v.file_link(&file));
+        let is_bot = client.is_some_and(|v| v.is_bot());
+
+        egui::Window::new("Share file")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Share \"{}\" without uploading it again.",
+                    file.path.name().unwrap_or("Cannot display this name")
+                ));
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(link.is_some(), Button::new("Copy link"))
+                        .on_disabled_hover_text("Only files in a supergroup or channel have links")
+                        .clicked()
+                    {
+                        if let Some(link) = link.clone() {
+                            ui.output_mut(|o| o.copied_text = link);
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        window.cloud_tab.pending_share = None;
+                    }
+                });
+                if is_bot {
+                    return;
+                }
+                ui.separator();
+
+                ui.label("Forward to:");
+                ui.add(
+                    TextEdit::singleline(&mut window.cloud_tab.share_filter)
+                        .hint_text("Search chats"),
+                );
+                let filter = window.cloud_tab.share_filter.to_lowercase();
+                let targets = window
+                    .cloud_tab
+                    .share_targets
+                    .get(&window.current_client)
+                    .into_iter()
+                    .flatten()
+                    .filter(|v| v.name.to_lowercase().contains(&filter))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for target in targets {
+                        if ui.selectable_label(false, &target.name).clicked() {
+                            window.cloud_tab.pending_share = None;
+                            NewProcess::ShareFile(file.clone(), target).start(window);
+                        }
+                    }
+                });
+            });
+    }
+
     pub fn ui(window: &mut Window, ctx: &Context) {
         window.header(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("Upload file").clicked() {
-                    if let Some(transferred_files) = rfd::FileDialog::new().pick_files() {
+                    let mut file_dialog = rfd::FileDialog::new();
+                    if !window.settings.upload_directory.is_empty() {
+                        file_dialog = file_dialog.set_directory(&window.settings.upload_directory);
+                    }
+                    if let Some(transferred_files) = file_dialog.pick_files() {
                         NewProcess::UploadFiles(transferred_files).start(window);
                     }
                 }
@@ -103,16 +352,22 @@ impl Cloud {
                                         |ui| {
                                             if ui.button("Download").clicked() {
                                                 NewProcess::DownloadFiles(
-                                                    dir.get_files_messages_ids(),
+                                                    dir.get_files_recursively(),
                                                 )
                                                 .start(window);
                                             }
-                                            if ui.button("Delete").clicked() {
-                                                NewProcess::DeleteFiles(
-                                                    dir.get_files_messages_ids(),
+                                            if ui.button("Verify").clicked() {
+                                                NewProcess::VerifyFiles(
+                                                    dir.get_files_recursively(),
                                                 )
                                                 .start(window);
                                             }
+                                            if ui.button("Delete").clicked() {
+                                                Self::delete(window, dir.get_files_recursively());
+                                            }
+                                            if ui.button("Copy").clicked() {
+                                                Self::copy(window, dir.get_files_recursively());
+                                            }
                                         },
                                     );
                                 });
@@ -121,20 +376,30 @@ impl Cloud {
 
                             for file in &relative_dir.files {
                                 ui.horizontal(|ui| {
-                                    ui.label(file.path.name().unwrap_or_else(|| {
-                                        "Cannot display this name"
-                                    }));
+                                    ui.label(file.path.name().unwrap_or("Cannot display this name"));
+                                    if let Some(uploader) = &file.uploader {
+                                        ui.weak(format!("by {}", uploader));
+                                    }
                                     ui.with_layout(
                                         Layout::right_to_left(eframe::egui::Align::Max),
                                         |ui| {
                                             if ui.button("Download").clicked() {
-                                                NewProcess::DownloadFiles(vec![file.message_id])
+                                                NewProcess::DownloadFiles(vec![file.clone()])
                                                     .start(window);
                                             }
-                                            if ui.button("Delete").clicked() {
-                                                NewProcess::DeleteFiles(vec![file.message_id])
+                                            if ui.button("Verify").clicked() {
+                                                NewProcess::VerifyFiles(vec![file.clone()])
                                                     .start(window);
                                             }
+                                            if ui.button("Share").clicked() {
+                                                Self::share(window, file.clone());
+                                            }
+                                            if ui.button("Copy").clicked() {
+                                                Self::copy(window, vec![file.clone()]);
+                                            }
+                                            if ui.button("Delete").clicked() {
+                                                Self::delete(window, vec![file.clone()]);
+                                            }
                                         },
                                     );
                                 });
@@ -144,6 +409,9 @@ impl Cloud {
                     });
             }
         });
+        Self::confirm_delete_ui(window, ctx);
+        Self::share_ui(window, ctx);
+        Self::copy_ui(window, ctx);
         window.footer(ctx);
     }
 }
@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use eframe::egui::{self, Button, Color32, ComboBox, Context, Grid, RichText, TextEdit};
+
+use crate::{
+    enums::{MemberRole, NewProcess},
+    types::Drive,
+    ui::window::Window,
+};
+
+pub struct Drives {
+    new_drive_name: String,
+    /// Names being typed in the rename fields, by drive name.
+    new_names: BTreeMap<String, String>,
+    pending_delete: Option<String>,
+    rebind_drive: String,
+    rebind_address: String,
+    member_username: String,
+    member_role: MemberRole,
+    shared_drive_name: String,
+    shared_drive_address: String,
+}
+
+impl Drives {
+    pub fn new() -> Self {
+        Self {
+            new_drive_name: String::new(),
+            new_names: BTreeMap::new(),
+            pending_delete: None,
+            rebind_drive: String::new(),
+            rebind_address: String::new(),
+            member_username: String::new(),
+            member_role: MemberRole::default(),
+            shared_drive_name: String::new(),
+            shared_drive_address: String::new(),
+        }
+    }
+
+    fn confirm_delete_ui(window: &mut Window, ctx: &Context) {
+        let name = match &window.drives_tab.pending_delete {
+            Some(v) => v.clone(),
+            None => return,
+        };
+
+        egui::Window::new("Delete drive")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Delete the drive \"{}\" and every file in it? This cannot be undone.",
+                    name
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        window.drives_tab.pending_delete = None;
+                        NewProcess::DeleteDrive(name).start(window);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        window.drives_tab.pending_delete = None;
+                    }
+                });
+            });
+    }
+
+    pub fn ui(window: &mut Window, ctx: &Context) {
+        window.header(ctx);
+        Self::confirm_delete_ui(window, ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let client = match window.get_current_client() {
+                Ok(v) => v,
+                Err(_) => {
+                    ui.label("There are no connected accounts.");
+                    return;
+                }
+            };
+            ui.label(format!(
+                "Drives of {}. Each drive is a separate chat on Telegram.",
+                window.client_label(&window.current_client)
+            ));
+            ui.separator();
+
+            Grid::new("Drives")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for drive in &client.drives {
+                        let label = Drive::label(&drive.name);
+                        if drive.name == client.drive {
+                            ui.strong(label);
+                        } else {
+                            ui.label(label);
+                        }
+
+                        if drive.name.is_empty() {
+                            ui.label("");
+                            ui.label("");
+                            ui.end_row();
+                            continue;
+                        }
+
+                        let new_name = window
+                            .drives_tab
+                            .new_names
+                            .entry(drive.name.clone())
+                            .or_insert_with(|| drive.name.clone());
+                        ui.add(TextEdit::singleline(new_name));
+                        let new_name = new_name.trim().to_string();
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    !new_name.is_empty() && new_name != drive.name,
+                                    Button::new("Rename"),
+                                )
+                                .clicked()
+                            {
+                                window.drives_tab.new_names.remove(&drive.name);
+                                NewProcess::RenameDrive(drive.name.clone(), new_name).start(window);
+                            }
+                            if ui.button("Delete").clicked() {
+                                window.drives_tab.pending_delete = Some(drive.name.clone());
+                            }
+                        });
+                        ui.end_row();
+                    }
+
+                    for name in &client.missing_drives {
+                        ui.label(RichText::new(Drive::label(name)).color(Color32::YELLOW))
+                            .on_hover_text(
+                                "The chat of this drive was deleted or cannot be accessed",
+                            );
+                        ui.label(RichText::new("Chat missing").color(Color32::YELLOW));
+
+                        ui.horizontal(|ui| {
+                            if !client.is_bot() && ui.button("Recreate").clicked() {
+                                NewProcess::RecreateDrive(name.clone()).start(window);
+                            }
+                            if !name.is_empty() && ui.button("Forget").clicked() {
+                                NewProcess::DeleteDrive(name.clone()).start(window);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Bind drive ");
+                ComboBox::from_id_source("rebind-drive")
+                    .selected_text(Drive::label(&window.drives_tab.rebind_drive))
+                    .show_ui(ui, |ui| {
+                        let names = client
+                            .drives
+                            .iter()
+                            .map(|v| &v.name)
+                            .chain(client.missing_drives.iter());
+                        for name in names {
+                            ui.selectable_value(
+                                &mut window.drives_tab.rebind_drive,
+                                name.clone(),
+                                Drive::label(name),
+                            );
+                        }
+                    });
+                ui.label(" to ");
+                ui.add(
+                    TextEdit::singleline(&mut window.drives_tab.rebind_address)
+                        .hint_text("@username or chat id"),
+                );
+                let address = window.drives_tab.rebind_address.trim().to_string();
+                if ui
+                    .add_enabled(!address.is_empty(), Button::new("Bind"))
+                    .on_hover_text("Store the files of the drive in another existing chat")
+                    .clicked()
+                {
+                    window.drives_tab.rebind_address.clear();
+                    NewProcess::RebindDrive(
+                        window.current_client.clone(),
+                        window.drives_tab.rebind_drive.clone(),
+                        address,
+                    )
+                    .start(window);
+                }
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label(format!("Share {} with ", Drive::label(&client.drive)));
+                ui.add(
+                    TextEdit::singleline(&mut window.drives_tab.member_username)
+                        .hint_text("@username"),
+                );
+                ui.label(" as ");
+                ComboBox::from_id_source("member-role")
+                    .selected_text(window.drives_tab.member_role.to_string())
+                    .show_ui(ui, |ui| {
+                        for role in MemberRole::ALL {
+                            ui.selectable_value(
+                                &mut window.drives_tab.member_role,
+                                role,
+                                role.to_string(),
+                            )
+                            .on_hover_text(role.description());
+                        }
+                    });
+                let username = window.drives_tab.member_username.trim().to_string();
+                if ui
+                    .add_enabled(!username.is_empty(), Button::new("Invite"))
+                    .on_hover_text("Add the user to the chat of the drive, or change their role")
+                    .clicked()
+                {
+                    window.drives_tab.member_username.clear();
+                    NewProcess::InviteMember(username, window.drives_tab.member_role).start(window);
+                }
+            });
+            if let Some(chat) = &client.chat {
+                ui.label(format!(
+                    "Members add it in their Drives tab as a shared drive with chat id {}.",
+                    chat.id()
+                ));
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Shared drive: ");
+                ui.add(
+                    TextEdit::singleline(&mut window.drives_tab.shared_drive_name)
+                        .hint_text("Name"),
+                );
+                ui.label(" in ");
+                ui.add(
+                    TextEdit::singleline(&mut window.drives_tab.shared_drive_address)
+                        .hint_text("@username or chat id"),
+                );
+                let name = window.drives_tab.shared_drive_name.trim().to_string();
+                let address = window.drives_tab.shared_drive_address.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty() && !address.is_empty(), Button::new("Add"))
+                    .on_hover_text("Add a drive someone else shared with you")
+                    .clicked()
+                {
+                    window.drives_tab.shared_drive_name.clear();
+                    window.drives_tab.shared_drive_address.clear();
+                    NewProcess::AddSharedDrive(name, address).start(window);
+                }
+            });
+
+            ui.separator();
+
+            if client.is_bot() {
+                ui.label("Bots store files in a single chat and cannot create drives.");
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label("New drive: ");
+                ui.add(
+                    TextEdit::singleline(&mut window.drives_tab.new_drive_name).hint_text("Photos"),
+                );
+                let name = window.drives_tab.new_drive_name.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty(), Button::new("Create"))
+                    .clicked()
+                {
+                    window.drives_tab.new_drive_name.clear();
+                    NewProcess::CreateDrive(name).start(window);
+                }
+            });
+        });
+        window.footer(ctx);
+    }
+}
@@ -1,11 +1,26 @@
+mod accounts;
 mod cloud;
+mod drives;
 mod new_session;
+mod settings;
+mod setup;
+mod transfers;
 
+pub use accounts::Accounts;
 pub use cloud::Cloud;
+pub use drives::Drives;
 pub use new_session::NewSession;
+pub use settings::Settings;
+pub use setup::Setup;
+pub use transfers::Transfers;
 
 #[derive(PartialEq)]
 pub enum Tab {
     NewSession,
     Cloud,
+    Transfers,
+    Setup,
+    Settings,
+    Accounts,
+    Drives,
 }
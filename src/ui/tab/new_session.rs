@@ -1,30 +1,59 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use eframe::egui::{self, Button, Context, Grid, Layout, TextEdit};
+use eframe::egui::{self, Button, Color32, Context, Grid, Layout, Rect, Sense, TextEdit, Ui, Vec2};
 use grammers_client::types::LoginToken;
+use qrcode::QrCode;
 
-use crate::{enums::NewProcess, types::Client, ui::window::Window};
+use crate::{
+    enums::{LoginMethod, NewProcess},
+    types::{Client, Drive, QrLogin},
+    ui::window::Window,
+};
+
+const QR_POLL_INTERVAL: Duration = Duration::from_secs(3);
+const QR_MODULE_SIZE: f32 = 6.0;
+/// White border around the code, in modules, required by scanners.
+const QR_QUIET_ZONE: usize = 4;
 
 pub struct NewSession {
-    pub new_session_name: String,
     pub phone_number: String,
     pub reveived_code: String,
     pub user_password: String,
     pub is_code_received: bool,
     pub login_token: Option<Arc<LoginToken>>,
     pub incomplete_client: Option<Client>,
+    pub login_method: LoginMethod,
+    pub qr_login: Option<QrLogin>,
+    pub is_qr_password_required: bool,
+    pub is_polling_qr: bool,
+    pub last_qr_poll: Option<Instant>,
+    pub bot_token: String,
+    pub bot_chat: String,
+    /// Signed in client whose account already has a cloud, waiting for the
+    /// choice to reuse it or create a new one.
+    pub existing_cloud: Option<Client>,
 }
 
 impl NewSession {
     pub fn new() -> Self {
         Self {
-            new_session_name: String::new(),
             phone_number: String::new(),
             reveived_code: String::new(),
             user_password: String::new(),
             is_code_received: false,
             login_token: None,
             incomplete_client: None,
+            login_method: LoginMethod::PhoneNumber,
+            qr_login: None,
+            is_qr_password_required: false,
+            is_polling_qr: false,
+            last_qr_poll: None,
+            bot_token: String::new(),
+            bot_chat: String::new(),
+            existing_cloud: None,
         }
     }
 
@@ -32,53 +61,219 @@ impl NewSession {
         window.header(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            Grid::new("New session").num_columns(2).show(ui, |ui| {
-                ui.label("Phone number: ");
+            if window.new_session_tab.existing_cloud.is_some() {
+                Self::existing_cloud_ui(window, ui);
+                return;
+            }
+            ui.horizontal(|ui| {
+                let login_method = &mut window.new_session_tab.login_method;
+                ui.selectable_value(login_method, LoginMethod::PhoneNumber, "Phone number");
+                ui.selectable_value(login_method, LoginMethod::QrCode, "QR code");
+                ui.selectable_value(login_method, LoginMethod::BotToken, "Bot token");
+            });
+            ui.separator();
+
+            match window.new_session_tab.login_method {
+                LoginMethod::PhoneNumber => Self::phone_number_ui(window, ui),
+                LoginMethod::QrCode => Self::qr_code_ui(window, ui),
+                LoginMethod::BotToken => Self::bot_token_ui(window, ui),
+            }
+        });
+        window.footer(ctx);
+    }
+
+    fn existing_cloud_ui(window: &mut Window, ui: &mut Ui) {
+        let drives = match &window.new_session_tab.existing_cloud {
+            Some(client) => client
+                .drives
+                .iter()
+                .map(|v| Drive::label(&v.name))
+                .collect::<Vec<_>>(),
+            None => return,
+        };
+
+        ui.label("This account already has a cloud with these drives:");
+        for drive in drives {
+            ui.label(format!("• {}", drive));
+        }
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            if ui
+                .button("Use existing cloud")
+                .on_hover_text("Keep storing files in the chats found")
+                .clicked()
+            {
+                NewProcess::ProvisionCloud(true).start(window);
+            }
+            if ui
+                .button("Create new cloud")
+                .on_hover_text("Start with an empty cloud, the chats found are left as they are")
+                .clicked()
+            {
+                NewProcess::ProvisionCloud(false).start(window);
+            }
+        });
+    }
+
+    fn phone_number_ui(window: &mut Window, ui: &mut Ui) {
+        Grid::new("New session").num_columns(2).show(ui, |ui| {
+            ui.label("Phone number: ");
+            ui.add_enabled(
+                !window.new_session_tab.is_code_received,
+                TextEdit::singleline(&mut window.new_session_tab.phone_number)
+                    .min_size(ui.available_size()),
+            );
+            ui.end_row();
+
+            ui.label("Password (if required): ");
+            ui.add(
+                TextEdit::singleline(&mut window.new_session_tab.user_password)
+                    .min_size(ui.available_size()),
+            );
+            ui.end_row();
+
+            ui.label("Received code: ");
+            ui.with_layout(Layout::right_to_left(eframe::egui::Align::Min), |ui| {
+                if ui.button("Send code").clicked() {
+                    NewProcess::SendLoginCode.start(window);
+                };
+
+                let received_code_singleline =
+                    TextEdit::singleline(&mut window.new_session_tab.reveived_code)
+                        .min_size(ui.available_size());
+
                 ui.add_enabled(
-                    !window.new_session_tab.is_code_received,
-                    TextEdit::singleline(&mut window.new_session_tab.phone_number)
-                        .min_size(ui.available_size()),
+                    window.new_session_tab.is_code_received,
+                    received_code_singleline,
                 );
-                ui.end_row();
+            });
+            ui.end_row();
 
-                ui.label("Password (if required): ");
-                ui.add(
-                    TextEdit::singleline(&mut window.new_session_tab.user_password)
-                        .min_size(ui.available_size()),
-                );
-                ui.end_row();
-
-                ui.label("Received code: ");
-                ui.with_layout(Layout::right_to_left(eframe::egui::Align::Min), |ui| {
-                    if ui.button("Send code").clicked() {
-                        NewProcess::SendLoginCode.start(window);
-                    };
-
-                    let received_code_singleline =
-                        TextEdit::singleline(&mut window.new_session_tab.reveived_code)
-                            .min_size(ui.available_size());
-
-                    ui.add_enabled(
-                        window.new_session_tab.is_code_received,
-                        received_code_singleline,
-                    );
-                });
-                ui.end_row();
-
-                let sign_in_button = Button::new("Sign in");
-                if ui
-                    .add_enabled(
-                        window.new_session_tab.is_code_received
-                            && window.new_session_tab.reveived_code.len() >= 5,
-                        sign_in_button,
-                    )
-                    .clicked()
-                {
-                    NewProcess::SingIn.start(window);
-                };
-                ui.end_row();
+            let sign_in_button = Button::new("Sign in");
+            if ui
+                .add_enabled(
+                    window.new_session_tab.is_code_received
+                        && window.new_session_tab.reveived_code.len() >= 5,
+                    sign_in_button,
+                )
+                .clicked()
+            {
+                NewProcess::SingIn.start(window);
+            };
+            ui.end_row();
+        });
+    }
+
+    fn qr_code_ui(window: &mut Window, ui: &mut Ui) {
+        let new_session_tab = &mut window.new_session_tab;
+
+        let qr_login = match &new_session_tab.qr_login {
+            Some(v) => v.clone(),
+            None => {
+                ui.label("Log in by scanning a QR code with Telegram on a phone where you are already logged in.");
+                if ui.button("Show QR code").clicked() {
+                    NewProcess::StartQrLogin.start(window);
+                }
+                return;
+            }
+        };
+
+        if new_session_tab.is_qr_password_required {
+            ui.label("The account is protected with two-step verification.");
+            ui.horizontal(|ui| {
+                ui.label("Password: ");
+                ui.add(TextEdit::singleline(&mut new_session_tab.user_password).password(true));
             });
+            if ui
+                .add_enabled(
+                    !new_session_tab.user_password.is_empty(),
+                    Button::new("Sign in"),
+                )
+                .clicked()
+            {
+                NewProcess::PollQrLogin.start(window);
+            }
+            return;
+        }
+
+        ui.label("In Telegram on your phone open Settings > Devices > Link Desktop Device and scan this code.");
+        Self::qr_code_image(ui, &qr_login.url);
+        ui.label(format!(
+            "The code is refreshed automatically, the current one expires in {} s.",
+            qr_login.expires_in().as_secs()
+        ));
+
+        // Exporting the token again both refreshes it and tells whether it was scanned.
+        let is_poll_due = new_session_tab
+            .last_qr_poll
+            .is_none_or(|v| v.elapsed() >= QR_POLL_INTERVAL);
+        if !new_session_tab.is_polling_qr && is_poll_due {
+            NewProcess::PollQrLogin.start(window);
+        }
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+
+    fn bot_token_ui(window: &mut Window, ui: &mut Ui) {
+        ui.label("A bot stores files in a supergroup or a channel where it is an admin.");
+        Grid::new("Bot session").num_columns(2).show(ui, |ui| {
+            ui.label("Bot token: ");
+            ui.add(
+                TextEdit::singleline(&mut window.new_session_tab.bot_token)
+                    .password(true)
+                    .min_size(ui.available_size()),
+            );
+            ui.end_row();
+
+            ui.label("Storage chat: ");
+            ui.add(
+                TextEdit::singleline(&mut window.new_session_tab.bot_chat)
+                    .hint_text("@username or -100... id")
+                    .min_size(ui.available_size()),
+            );
+            ui.end_row();
+
+            let new_session_tab = &window.new_session_tab;
+            if ui
+                .add_enabled(
+                    !new_session_tab.bot_token.trim().is_empty()
+                        && !new_session_tab.bot_chat.trim().is_empty(),
+                    Button::new("Sign in"),
+                )
+                .clicked()
+            {
+                NewProcess::BotSignIn.start(window);
+            }
+            ui.end_row();
         });
-        window.footer(ctx);
+    }
+
+    fn qr_code_image(ui: &mut Ui, url: &str) {
+        let code = match QrCode::new(url.as_bytes()) {
+            Ok(v) => v,
+            Err(err) => {
+                ui.label(format!("Cannot show the QR code: {}", err));
+                return;
+            }
+        };
+        let width = code.width();
+        let modules = width + 2 * QR_QUIET_ZONE;
+        let size = modules as f32 * QR_MODULE_SIZE;
+
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(size), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::WHITE);
+
+        for (index, color) in code.to_colors().into_iter().enumerate() {
+            if color == qrcode::Color::Dark {
+                let x = (index % width + QR_QUIET_ZONE) as f32 * QR_MODULE_SIZE;
+                let y = (index / width + QR_QUIET_ZONE) as f32 * QR_MODULE_SIZE;
+                let min = rect.min + Vec2::new(x, y);
+                painter.rect_filled(
+                    Rect::from_min_size(min, Vec2::splat(QR_MODULE_SIZE)),
+                    0.0,
+                    Color32::BLACK,
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,84 @@
+use eframe::egui::{self, Button, Context, Grid, TextEdit};
+
+use crate::{
+    enums::{ErrorKind, NewProcess},
+    types::{APIKeys, ProcessError},
+    ui::{tab::Tab, window::Window},
+};
+
+pub struct Setup {
+    pub api_id: String,
+    pub api_hash: String,
+    /// Keys are already saved and the user came from Settings to replace them.
+    pub is_changing: bool,
+}
+
+impl Setup {
+    pub fn new() -> Self {
+        Self {
+            api_id: String::new(),
+            api_hash: String::new(),
+            is_changing: false,
+        }
+    }
+
+    pub fn api_keys(&self) -> Result<APIKeys, ProcessError> {
+        let api_id = self
+            .api_id
+            .trim()
+            .parse()
+            .map_err(|err| ErrorKind::InvalidAPIKeys.with_source(err))?;
+
+        Ok(APIKeys::new(api_id, self.api_hash.trim().to_string()))
+    }
+
+    pub fn ui(window: &mut Window, ctx: &Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if window.setup_tab.is_changing {
+                if ui.button("Cancel").clicked() {
+                    window.setup_tab = Setup::new();
+                    window.tab = Tab::Settings;
+                }
+                ui.separator();
+            }
+            ui.heading("Welcome to tucha");
+            ui.label("tucha needs the API ID and API hash of your Telegram application.");
+            ui.hyperlink_to(
+                "Get them on my.telegram.org",
+                "https://my.telegram.org/apps",
+            );
+            ui.separator();
+
+            Grid::new("Setup").num_columns(2).show(ui, |ui| {
+                ui.label("API ID: ");
+                ui.add(
+                    TextEdit::singleline(&mut window.setup_tab.api_id)
+                        .min_size(ui.available_size()),
+                );
+                ui.end_row();
+
+                ui.label("API hash: ");
+                ui.add(
+                    TextEdit::singleline(&mut window.setup_tab.api_hash)
+                        .password(true)
+                        .min_size(ui.available_size()),
+                );
+                ui.end_row();
+
+                let save_button = Button::new("Test connection and save");
+                if ui
+                    .add_enabled(
+                        !window.setup_tab.api_id.trim().is_empty()
+                            && !window.setup_tab.api_hash.trim().is_empty(),
+                        save_button,
+                    )
+                    .clicked()
+                {
+                    NewProcess::SaveAPIKeys.start(window);
+                }
+                ui.end_row();
+            });
+        });
+        window.footer(ctx);
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::atomic::Ordering;
+
+use eframe::egui::{
+    self, Button, CollapsingHeader, Color32, Context, DragValue, Grid, Layout, ProgressBar,
+    Response, RichText, Ui,
+};
+
+use crate::{
+    enums::{JobStatus, NewProcess},
+    types::{BandwidthSchedule, RateLimit, ScrubReport},
+    ui::window::Window,
+};
+
+pub struct Transfers {
+    pub scrub_history: Vec<ScrubReport>,
+    scrub_sample_size: usize,
+}
+
+impl Transfers {
+    pub fn new() -> Self {
+        Self {
+            scrub_history: ScrubReport::load_history(),
+            scrub_sample_size: 0,
+        }
+    }
+
+    pub fn ui(window: &mut Window, ctx: &Context) {
+        window.header(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Parallel transfers: ");
+                if ui
+                    .add(DragValue::new(&mut window.settings.parallelism).clamp_range(1..=16))
+                    .changed()
+                {
+                    window.apply_settings();
+                }
+
+                ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+                    if ui.button("Clear finished").clicked() {
+                        window.transfer_queue.clear_finished();
+                    }
+                });
+            });
+
+            Self::bandwidth_limits_ui(window, ui);
+            Self::scrub_ui(window, ui);
+
+            ui.separator();
+
+            let queue = &mut window.transfer_queue;
+            let jobs = queue
+                .jobs
+                .iter()
+                .map(|job| {
+                    (
+                        job.id,
+                        if job.drive.is_empty() {
+                            format!("{} ({})", job.kind, job.client_name)
+                        } else {
+                            format!("{} ({}, {})", job.kind, job.client_name, job.drive)
+                        },
+                        job.status.clone(),
+                        job.progress,
+                        job.rate_limit.clone(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            Grid::new("Transfers")
+                .num_columns(5)
+                .striped(true)
+                .max_col_width(ui.available_width())
+                .show(ui, |ui| {
+                    for (job_id, label, status, progress, rate_limit) in jobs {
+                        ui.label(label);
+                        ui.add(
+                            ProgressBar::new(progress)
+                                .desired_width(120.0)
+                                .show_percentage(),
+                        );
+                        ui.label(status.to_string());
+
+                        let mut job_limit = rate_limit.load(Ordering::Relaxed);
+                        if rate_limit_drag_value(ui, &mut job_limit).changed() {
+                            rate_limit.store(job_limit, Ordering::Relaxed);
+                        }
+
+                        ui.horizontal(|ui| match status {
+                            JobStatus::Queued => {
+                                if ui.button("Up").clicked() {
+                                    queue.move_up(job_id);
+                                }
+                                if ui.button("Down").clicked() {
+                                    queue.move_down(job_id);
+                                }
+                                if ui.button("Pause").clicked() {
+                                    queue.pause(job_id);
+                                }
+                            }
+                            JobStatus::Running | JobStatus::Waiting { .. } => {
+                                if ui.button("Pause").clicked() {
+                                    queue.pause(job_id);
+                                }
+                            }
+                            JobStatus::Paused => {
+                                if ui.button("Resume").clicked() {
+                                    queue.requeue(job_id);
+                                }
+                                if ui.button("Remove").clicked() {
+                                    queue.remove(job_id);
+                                }
+                            }
+                            JobStatus::Failed(_) => {
+                                if ui.button("Retry").clicked() {
+                                    queue.requeue(job_id);
+                                }
+                                if ui.button("Remove").clicked() {
+                                    queue.remove(job_id);
+                                }
+                            }
+                            JobStatus::Done => {}
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+        window.footer(ctx);
+    }
+
+    fn scrub_ui(window: &mut Window, ui: &mut Ui) {
+        CollapsingHeader::new("Scrub").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Files to check: ");
+                ui.add(
+                    DragValue::new(&mut window.transfers_tab.scrub_sample_size)
+                        .custom_formatter(|v, _| match v as usize {
+                            0 => "All".to_string(),
+                            v => v.to_string(),
+                        })
+                        .custom_parser(|text| text.trim().parse().ok()),
+                );
+
+                if ui
+                    .add_enabled(!window.clients.is_empty(), Button::new("Start scrub"))
+                    .clicked()
+                {
+                    let sample_size = match window.transfers_tab.scrub_sample_size {
+                        0 => None,
+                        v => Some(v),
+                    };
+                    NewProcess::Scrub(sample_size).start(window);
+                }
+            });
+
+            for report in window.transfers_tab.scrub_history.iter().rev() {
+                let text = RichText::new(report.to_string());
+                CollapsingHeader::new(match report.is_healthy() {
+                    true => text,
+                    false => text.color(Color32::RED),
+                })
+                .id_source(&report.started_at)
+                .show(ui, |ui| {
+                    for path in &report.mismatched {
+                        ui.label(format!("Mismatched: {}", path));
+                    }
+                    for (path, error) in &report.unreadable {
+                        ui.label(format!("Unreadable: {} ({})", path, error));
+                    }
+                });
+            }
+        });
+    }
+
+    fn bandwidth_limits_ui(window: &mut Window, ui: &mut Ui) {
+        let mut bandwidth_limits = window.settings.bandwidth_limits.clone();
+        let mut is_changed = false;
+
+        CollapsingHeader::new("Bandwidth limits").show(ui, |ui| {
+            Grid::new("Bandwidth limits")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Hours");
+                    ui.label("Upload");
+                    ui.label("Download");
+                    ui.end_row();
+
+                    ui.label("Always");
+                    is_changed |= rate_limit_ui(ui, &mut bandwidth_limits.global);
+                    ui.end_row();
+
+                    let mut removed_schedule = None;
+                    for (index, schedule) in bandwidth_limits.schedules.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            is_changed |= ui
+                                .add(DragValue::new(&mut schedule.start_hour).clamp_range(0..=23))
+                                .changed();
+                            ui.label("-");
+                            is_changed |= ui
+                                .add(DragValue::new(&mut schedule.end_hour).clamp_range(0..=24))
+                                .changed();
+                        });
+                        is_changed |= rate_limit_ui(ui, &mut schedule.limit);
+                        if ui.button("Remove").clicked() {
+                            removed_schedule = Some(index);
+                        }
+                        ui.end_row();
+                    }
+
+                    if let Some(index) = removed_schedule {
+                        bandwidth_limits.schedules.remove(index);
+                        is_changed = true;
+                    }
+                });
+
+            if ui.button("Add schedule").clicked() {
+                bandwidth_limits.schedules.push(BandwidthSchedule {
+                    start_hour: 9,
+                    end_hour: 18,
+                    limit: RateLimit::default(),
+                });
+                is_changed = true;
+            }
+        });
+
+        if is_changed {
+            window.settings.bandwidth_limits = bandwidth_limits;
+            window.apply_settings();
+        }
+    }
+}
+
+fn rate_limit_ui(ui: &mut Ui, rate_limit: &mut RateLimit) -> bool {
+    let is_upload_changed = rate_limit_drag_value(ui, &mut rate_limit.upload).changed();
+    let is_download_changed = rate_limit_drag_value(ui, &mut rate_limit.download).changed();
+    is_upload_changed || is_download_changed
+}
+
+/// Edits a rate in bytes per second as KB/s, where `0` is shown as unlimited.
+fn rate_limit_drag_value(ui: &mut Ui, bytes_per_second: &mut u64) -> Response {
+    let mut kilobytes_per_second = *bytes_per_second / 1024;
+    let response = ui.add(
+        DragValue::new(&mut kilobytes_per_second)
+            .speed(16)
+            .custom_formatter(|v, _| match v as u64 {
+                0 => "Unlimited".to_string(),
+                v => format!("{} KB/s", v),
+            })
+            .custom_parser(|text| text.trim().trim_end_matches("KB/s").trim().parse().ok()),
+    );
+    if response.changed() {
+        *bytes_per_second = kilobytes_per_second * 1024;
+    }
+    response
+}
@@ -1,13 +1,14 @@
 use std::{
     collections::BTreeMap,
     sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
 };
 
-use eframe::egui::{self, Color32, ComboBox, Layout, RichText, Spinner};
+use eframe::egui::{self, Color32, ComboBox, Layout, RichText, Spinner, Visuals};
 
-use crate::{enums::*, types::*};
+use crate::{enums::*, logging, types::*};
 
-use super::tab::{Cloud, NewSession, Tab};
+use super::tab::{self, Accounts, Cloud, Drives, NewSession, Setup, Tab, Transfers};
 
 pub struct Window {
     pub sender: Sender<ProcessResult>,
@@ -18,36 +19,185 @@ pub struct Window {
     pub current_process: CurrentProcess,
     pub new_session_tab: NewSession,
     pub cloud_tab: Cloud,
+    pub accounts_tab: Accounts,
+    pub drives_tab: Drives,
+    pub transfers_tab: Transfers,
+    pub setup_tab: Setup,
+    pub transfer_queue: TransferQueue,
+    pub settings: Settings,
+    pub session_statuses: BTreeMap<String, SessionStatus>,
 }
 
 impl Window {
     pub fn get_current_client(&self) -> Result<Client, ProcessError> {
         match self.clients.get(&self.current_client) {
             Some(v) => Ok(v.clone()),
-            None => Err(ProcessError::CurrentClientIsNone),
+            None => Err(ErrorKind::CurrentClientIsNone.into()),
         }
     }
 
+    /// A connected client, including one whose cloud chat is missing.
+    pub fn session_client(&self, session_id: &str) -> Option<Client> {
+        match (self.clients.get(session_id), self.session_statuses.get(session_id)) {
+            (Some(client), _) => Some(client.clone()),
+            (None, Some(SessionStatus::CloudChatMissing(client))) => Some(client.as_ref().clone()),
+            _ => None,
+        }
+    }
+
+    /// Drive selected for the current client, empty for the main one.
+    pub fn current_drive(&self) -> String {
+        self.clients
+            .get(&self.current_client)
+            .map(|v| v.drive.clone())
+            .unwrap_or_default()
+    }
+
+    /// Makes `name` the drive of the current client shown in the Cloud tab.
+    pub fn select_drive(&mut self, name: &str) {
+        if let Some(client) = self.clients.get_mut(&self.current_client) {
+            if client.select_drive(name) {
+                self.cloud_tab.clients_roots.remove(&self.current_client);
+                self.cloud_tab.current_path = Path::default();
+                NewProcess::GetUploadedFiles.start(self);
+            }
+        }
+    }
+
+    /// Saves the settings and applies the ones that are not read on demand.
+    pub fn apply_settings(&mut self) {
+        self.transfer_queue.parallelism = self.settings.parallelism;
+        if let Ok(mut v) = self.transfer_queue.bandwidth_limits.write() {
+            *v = self.settings.bandwidth_limits.clone();
+        }
+
+        if let Err(err) = self.settings.save() {
+            self.current_process = CurrentProcess::Error(err);
+        }
+    }
+
+    /// Makes a connected session usable and records how loading every session went.
+    pub fn add_session(&mut self, session_id: String, result: Result<Client, SessionStatus>) {
+        let status = match result {
+            Ok(client) => {
+                self.clients.insert(session_id.clone(), client);
+                SessionStatus::Connected
+            }
+            Err(status) => status,
+        };
+        self.session_statuses.insert(session_id, status);
+        self.accounts_tab.refresh_sessions();
+    }
+
+    /// The client marked as the default account, if it is connected.
+    pub fn default_client(&self) -> Option<String> {
+        self.settings
+            .default_account
+            .clone()
+            .filter(|v| self.clients.contains_key(v))
+    }
+
+    /// Label set in the Accounts tab, otherwise the username, full name or phone number.
+    pub fn client_label(&self, session_id: &str) -> String {
+        if let Some(label) = self.settings.account_labels.get(session_id) {
+            return label.clone();
+        }
+        match self.clients.get(session_id) {
+            Some(client) => client.display_name(),
+            None => session_id.to_string(),
+        }
+    }
+
+    pub fn remove_client(&mut self, client_name: &str) {
+        self.clients.remove(client_name);
+        self.cloud_tab.clients_roots.remove(client_name);
+
+        if self.current_client == client_name {
+            self.current_client = self
+                .default_client()
+                .or_else(|| self.clients.keys().next().cloned())
+                .unwrap_or_default();
+
+            if !self.current_client.is_empty() {
+                NewProcess::GetUploadedFiles.start(self);
+            }
+        }
+    }
+
+    /// Drops the label, default mark and transfers of a session that no longer exists.
+    pub fn forget_session(&mut self, session_id: &str) {
+        self.session_statuses.remove(session_id);
+        self.transfer_queue.remove_client_jobs(session_id);
+        self.accounts_tab.refresh_sessions();
+        self.settings.account_labels.remove(session_id);
+        self.settings.account_proxies.remove(session_id);
+        if self.settings.default_account.as_deref() == Some(session_id) {
+            self.settings.default_account = None;
+        }
+        self.apply_settings();
+    }
+
     pub fn header(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("tab").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.tab, Tab::Cloud, "Cloud");
+                ui.selectable_value(&mut self.tab, Tab::Drives, "Drives");
                 ui.selectable_value(&mut self.tab, Tab::NewSession, "New session");
+                let broken_sessions = self
+                    .session_statuses
+                    .values()
+                    .filter(|status| !status.is_connected())
+                    .count();
+                let accounts_label = if broken_sessions > 0 {
+                    format!("Accounts ({} need attention)", broken_sessions)
+                } else {
+                    "Accounts".to_string()
+                };
+                if ui
+                    .selectable_value(&mut self.tab, Tab::Accounts, accounts_label)
+                    .clicked()
+                {
+                    self.accounts_tab.refresh_sessions();
+                }
+                ui.selectable_value(
+                    &mut self.tab,
+                    Tab::Transfers,
+                    format!("Transfers ({})", self.transfer_queue.running_count()),
+                );
+                ui.selectable_value(&mut self.tab, Tab::Settings, "Settings");
 
                 ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
-                    if self.clients.len() > 0 {
+                    if ui
+                        .button("Copy diagnostics")
+                        .on_hover_text("Copy version info and recent logs for a bug report")
+                        .clicked()
+                    {
+                        let diagnostics = logging::diagnostics(
+                            self.clients.len(),
+                            &[
+                                &self.new_session_tab.phone_number,
+                                &self.new_session_tab.reveived_code,
+                                &self.new_session_tab.user_password,
+                                &self.new_session_tab.bot_token,
+                            ],
+                        );
+                        ui.output_mut(|o| o.copied_text = diagnostics);
+                    }
+                    if !self.clients.is_empty() {
+                        let labels = self
+                            .clients
+                            .keys()
+                            .map(|name| (name.clone(), self.client_label(name)))
+                            .collect::<Vec<(String, String)>>();
+
                         ComboBox::from_id_source("current-client")
-                            .selected_text(format!("{}", self.current_client))
+                            .selected_text(self.client_label(&self.current_client))
                             .show_ui(ui, |ui| {
                                 let mut is_changed = false;
-                                for client in &self.clients {
+                                for (name, label) in labels {
                                     if !is_changed
                                         && ui
-                                            .selectable_value(
-                                                &mut self.current_client,
-                                                client.0.to_string(),
-                                                client.0,
-                                            )
+                                            .selectable_value(&mut self.current_client, name, label)
                                             .changed()
                                     {
                                         is_changed = true;
@@ -57,8 +207,34 @@ impl Window {
                                     NewProcess::GetUploadedFiles.start(self);
                                 }
                             });
+                        let drives = self
+                            .clients
+                            .get(&self.current_client)
+                            .map(|client| {
+                                client
+                                    .drives
+                                    .iter()
+                                    .map(|drive| drive.name.clone())
+                                    .collect::<Vec<String>>()
+                            })
+                            .unwrap_or_default();
+                        let current_drive = self.current_drive();
+                        let mut selected_drive = current_drive.clone();
+
+                        ComboBox::from_id_source("current-drive")
+                            .selected_text(Drive::label(&current_drive))
+                            .show_ui(ui, |ui| {
+                                for name in drives {
+                                    let label = Drive::label(&name).to_string();
+                                    ui.selectable_value(&mut selected_drive, name, label);
+                                }
+                            });
+                        if selected_drive != current_drive {
+                            self.select_drive(&selected_drive);
+                        }
                         if ui.button("Restart clients").clicked() {
                             self.clients.clear();
+                            self.session_statuses.clear();
                             self.cloud_tab.clients_roots.clear();
                             NewProcess::ConnectToAllSavedClients.start(self);
                         }
@@ -74,7 +250,7 @@ impl Window {
                 ui.horizontal(|ui| {
                     if let CurrentProcess::Error(error) = &self.current_process {
                         ui.label(
-                            RichText::new(&error.to_string())
+                            RichText::new(error.to_string())
                                 .color(Color32::RED)
                                 .strong(),
                         );
@@ -83,6 +259,31 @@ impl Window {
                                 self.current_process = CurrentProcess::Idle;
                             }
                         });
+                    } else if let CurrentProcess::ConnectionTested(_) = &self.current_process {
+                        ui.label(
+                            RichText::new(self.current_process.to_string())
+                                .color(Color32::GREEN)
+                                .strong(),
+                        );
+                        ui.with_layout(Layout::right_to_left(egui::Align::Max), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.current_process = CurrentProcess::Idle;
+                            }
+                        });
+                    } else if let CurrentProcess::PartiallyFailed { errors, .. } =
+                        &self.current_process
+                    {
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(self.current_process.to_string()).strong());
+                            for error in errors {
+                                ui.label(RichText::new(error.to_string()).color(Color32::RED));
+                            }
+                        });
+                        ui.with_layout(Layout::right_to_left(egui::Align::Max), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.current_process = CurrentProcess::Idle;
+                            }
+                        });
                     } else {
                         ui.add(Spinner::new());
                         ui.label(self.current_process.to_string());
@@ -94,11 +295,34 @@ impl Window {
 }
 
 impl eframe::App for Window {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         ProcessResult::check_result(self);
+
+        let system_is_dark = frame.info().system_theme.map(|v| v == eframe::Theme::Dark);
+        let is_dark = self.settings.theme.is_dark(system_is_dark);
+        if ctx.style().visuals.dark_mode != is_dark {
+            ctx.set_visuals(if is_dark {
+                Visuals::dark()
+            } else {
+                Visuals::light()
+            });
+        }
+
+        for job_id in self.transfer_queue.jobs_to_start() {
+            NewProcess::RunJob(job_id).start(self);
+        }
+        if self.transfer_queue.running_count() > 0 {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
         match &self.tab {
             Tab::NewSession => NewSession::ui(self, ctx),
             Tab::Cloud => Cloud::ui(self, ctx),
+            Tab::Transfers => Transfers::ui(self, ctx),
+            Tab::Setup => Setup::ui(self, ctx),
+            Tab::Accounts => Accounts::ui(self, ctx),
+            Tab::Drives => Drives::ui(self, ctx),
+            Tab::Settings => tab::Settings::ui(self, ctx),
         }
     }
 }
@@ -106,6 +330,12 @@ impl eframe::App for Window {
 impl Default for Window {
     fn default() -> Self {
         let (sender, receiver) = mpsc::channel();
+        let settings = Settings::load();
+        let mut transfer_queue = TransferQueue::new();
+        transfer_queue.parallelism = settings.parallelism;
+        if let Ok(mut v) = transfer_queue.bandwidth_limits.write() {
+            *v = settings.bandwidth_limits.clone();
+        }
 
         let mut window = Self {
             sender,
@@ -116,9 +346,20 @@ impl Default for Window {
             current_process: CurrentProcess::Idle,
             new_session_tab: NewSession::new(),
             cloud_tab: Cloud::new(),
+            accounts_tab: Accounts::new(),
+            drives_tab: Drives::new(),
+            transfers_tab: Transfers::new(),
+            setup_tab: Setup::new(),
+            transfer_queue,
+            settings,
+            session_statuses: BTreeMap::new(),
         };
 
-        NewProcess::ConnectToAllSavedClients.start(&mut window);
+        if APIKeys::load().is_some() {
+            NewProcess::ConnectToAllSavedClients.start(&mut window);
+        } else {
+            window.tab = Tab::Setup;
+        }
 
         window
     }
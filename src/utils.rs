@@ -1,9 +1,12 @@
 use std::sync::mpsc::Sender;
 
-use dirs::home_dir;
-use grammers_client::types::Message;
+use sha2::{Digest, Sha256};
+use tokio::{fs::File, io::AsyncReadExt};
 
-use crate::enums::{ProcessError, ProcessResult};
+use crate::{
+    enums::{ErrorKind, ProcessResult},
+    types::{ProcessError, PART_SIZE},
+};
 
 pub fn send_result(
     sender: Sender<ProcessResult>,
@@ -19,17 +22,82 @@ pub fn send_result(
     }
 }
 
-pub fn get_home_directory() -> Result<String, ProcessError> {
-    match home_dir() {
-        Some(v) => Ok(v.display().to_string()),
-        None => return Err(ProcessError::HomeDirectoryIsNone),
+/// Reads the next part of `PART_SIZE` bytes, or less if the end of the file is reached.
+pub async fn read_part(file: &mut File) -> Result<Vec<u8>, ProcessError> {
+    let mut part = vec![0; PART_SIZE];
+    let mut filled = 0;
+
+    while filled < PART_SIZE {
+        let read = file
+            .read(&mut part[filled..])
+            .await
+            .map_err(|err| ErrorKind::CannotReadFile.with_source(err))?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
     }
+    part.truncate(filled);
+
+    Ok(part)
 }
 
-pub async fn download_file(message: Message, name: &str) -> Result<(), ProcessError> {
-    message
-        .download_media(format!("{}/Downloads/{}", get_home_directory()?, name))
+pub async fn hash_file(path: impl AsRef<std::path::Path>) -> Result<String, ProcessError> {
+    let mut file = File::open(path)
         .await
-        .map_err(|_| ProcessError::CannotDownloadMedia)?;
-    Ok(())
+        .map_err(|err| ErrorKind::CannotReadFile.with_source(err))?;
+    let mut hasher = Sha256::new();
+
+    loop {
+        let part = read_part(&mut file).await?;
+        if part.is_empty() {
+            break;
+        }
+        hasher.update(&part);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Creates an empty file at `path`, or at `name (1).ext`, `name (2).ext` and so on
+/// if it is taken, and returns its path. Downloads are moved over the reserved file,
+/// so two files of the same name never overwrite each other.
+pub async fn reserve_path(path: &str) -> Result<String, ProcessError> {
+    for number in 0.. {
+        let candidate = numbered_path(path, number);
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+            .await
+        {
+            Ok(_) => return Ok(candidate),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(ErrorKind::CannotWriteFile.with_source(err)),
+        }
+    }
+    unreachable!("every number is taken")
+}
+
+fn numbered_path(path: &str, number: u32) -> String {
+    if number == 0 {
+        return path.to_string();
+    }
+    let path = std::path::Path::new(path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{} ({}).{}", stem, number, extension.to_string_lossy()),
+        None => format!("{} ({})", stem, number),
+    };
+    path.with_file_name(name).display().to_string()
+}
+
+/// Compares the file with the expected hash. Files without a recorded hash always pass.
+pub async fn check_hash(path: &str, expected_hash: &Option<String>) -> Result<(), ProcessError> {
+    match expected_hash {
+        Some(expected_hash) if *expected_hash != hash_file(path).await? => {
+            Err(ErrorKind::HashMismatch.into())
+        }
+        _ => Ok(()),
+    }
 }